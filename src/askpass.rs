@@ -0,0 +1,103 @@
+use std::fs::OpenOptions;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Environment variables the installed askpass script answers from.
+pub const USERNAME_VAR: &str = "REPO_CLONER_USERNAME";
+pub const PASSWORD_VAR: &str = "REPO_CLONER_PASSWORD";
+
+/// git runs `$GIT_ASKPASS "<prompt>"` and reads the answer from stdout. The
+/// credentials come from the environment so they never touch the disk.
+const SCRIPT: &str = r#"#!/bin/sh
+case "$1" in
+    Username*) printf '%s\n' "$REPO_CLONER_USERNAME" ;;
+    *) printf '%s\n' "$REPO_CLONER_PASSWORD" ;;
+esac
+"#;
+
+/// Writes the askpass script to a new file in `dir`, executable only by its
+/// owner, and returns its path. The file is created exclusively with its
+/// final permissions, so a file or symlink someone else planted at that
+/// name in a shared `dir` makes this fail instead of being written through.
+pub fn install_script(dir: &Path) -> io::Result<PathBuf> {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.subsec_nanos());
+    let path = dir.join(format!(
+        "repo-cloner-askpass-{}-{}.sh",
+        std::process::id(),
+        nanos
+    ));
+    write_script(&path)?;
+    Ok(path)
+}
+
+fn write_script(path: &Path) -> io::Result<()> {
+    let mut options = OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o700);
+    }
+    options.open(path)?.write_all(SCRIPT.as_bytes())
+}
+
+/// The environment that makes git answer credential prompts with
+/// `username` and `password` through the script at `script`.
+pub fn credential_env(script: &Path, username: &str, password: &str) -> Vec<(String, String)> {
+    vec![
+        (
+            "GIT_ASKPASS".to_string(),
+            script.to_string_lossy().into_owned(),
+        ),
+        (USERNAME_VAR.to_string(), username.to_string()),
+        (PASSWORD_VAR.to_string(), password.to_string()),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::process::Command;
+
+    #[cfg(unix)]
+    #[test]
+    fn test_script_answers_git_prompts() {
+        let dir = tempfile::tempdir().unwrap();
+        let script = install_script(dir.path()).unwrap();
+        let ask = |prompt: &str| {
+            let output = Command::new(&script)
+                .arg(prompt)
+                .envs(credential_env(&script, "alice", "s3cret pass"))
+                .output()
+                .unwrap();
+            String::from_utf8(output.stdout).unwrap()
+        };
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(&script).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o700);
+        }
+
+        assert_eq!(ask("Username for 'https://github.com': "), "alice\n");
+        assert_eq!(
+            ask("Password for 'https://alice@github.com': "),
+            "s3cret pass\n"
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_install_script_does_not_follow_planted_symlinks() {
+        let dir = tempfile::tempdir().unwrap();
+        let target = dir.path().join("victim");
+        std::fs::write(&target, "original").unwrap();
+        let script = dir.path().join("askpass.sh");
+        std::os::unix::fs::symlink(&target, &script).unwrap();
+
+        assert!(write_script(&script).is_err());
+        assert_eq!(std::fs::read_to_string(&target).unwrap(), "original");
+    }
+}
@@ -0,0 +1,79 @@
+use crate::{CloneOptions, RepoCommands};
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Keeps one bare clone per URL under a cache directory so later clones of
+/// the same URL can borrow its objects with `--reference`.
+pub struct CacheManager<'a, C: RepoCommands> {
+    commands: &'a C,
+    cache_dir: &'a Path,
+    credentials: Option<(String, String)>,
+}
+
+impl<'a, C: RepoCommands> CacheManager<'a, C> {
+    pub fn new(commands: &'a C, cache_dir: &'a Path) -> Self {
+        CacheManager {
+            commands,
+            cache_dir,
+            credentials: None,
+        }
+    }
+
+    /// Clones into the cache with these credentials, like the main clone.
+    pub fn with_credentials(mut self, credentials: Option<(String, String)>) -> Self {
+        self.credentials = credentials;
+        self
+    }
+
+    /// Where the bare clone of `url` lives: `<cache_dir>/<hash of url>`.
+    pub fn entry_path(&self, url: &str) -> PathBuf {
+        self.cache_dir.join(cache_key(url))
+    }
+
+    /// Returns the cached bare clone of `url`, creating it first if this is
+    /// the first time `url` is cloned with this cache.
+    pub fn get_or_create_reference(&self, url: &str) -> io::Result<PathBuf> {
+        let entry = self.entry_path(url);
+        if !self.commands.path_exists(&entry) {
+            self.commands.create_dir_all(self.cache_dir)?;
+            let bare = CloneOptions {
+                bare: true,
+                credentials: self.credentials.clone(),
+                ..Default::default()
+            };
+            self.commands.git_clone(url, &entry, &bare)?;
+            // As for the main clone, don't leave the password in the config.
+            if bare.credentials.is_some() {
+                self.commands.git_config(&entry, "remote.origin.url", url)?;
+            }
+        }
+        Ok(entry)
+    }
+}
+
+/// A 64-bit FNV-1a hash of `url` in hex. Unlike `DefaultHasher`, it is
+/// stable across Rust releases, so cache entries survive toolchain upgrades.
+fn cache_key(url: &str) -> String {
+    let hash = url.bytes().fold(0xcbf2_9ce4_8422_2325_u64, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    });
+    format!("{:016x}", hash)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cache_key_is_stable_and_distinct() {
+        assert_eq!(cache_key(""), "cbf29ce484222325");
+        assert_eq!(
+            cache_key("https://github.com/author/project.git"),
+            cache_key("https://github.com/author/project.git")
+        );
+        assert_ne!(
+            cache_key("https://github.com/author/project.git"),
+            cache_key("https://github.com/author/other.git")
+        );
+    }
+}
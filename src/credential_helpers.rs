@@ -0,0 +1,65 @@
+use url::Url;
+
+/// The AWS region in an AWS CodeCommit HTTPS URL such as
+/// `https://git-codecommit.us-east-2.amazonaws.com/v1/repos/project`.
+#[cfg(feature = "aws")]
+pub fn codecommit_region(git_url: &str) -> Option<String> {
+    let url = Url::parse(git_url).ok()?;
+    let region = url
+        .host_str()?
+        .strip_prefix("git-codecommit.")?
+        .strip_suffix(".amazonaws.com")?;
+    (!region.is_empty() && !region.contains('.')).then(|| region.to_string())
+}
+
+/// Whether `git_url` points at Azure DevOps (Azure Repos), either on
+/// `dev.azure.com` or on a legacy `<organization>.visualstudio.com` host.
+pub fn is_azure_devops_url(git_url: &str) -> bool {
+    let Some(host) = Url::parse(git_url)
+        .ok()
+        .and_then(|url| url.host_str().map(str::to_ascii_lowercase))
+    else {
+        return false;
+    };
+    host == "dev.azure.com" || host == "ssh.dev.azure.com" || host.ends_with(".visualstudio.com")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_azure_devops_url() {
+        assert!(is_azure_devops_url(
+            "https://org@dev.azure.com/org/project/_git/repo"
+        ));
+        assert!(is_azure_devops_url(
+            "https://org.visualstudio.com/project/_git/repo"
+        ));
+        assert!(is_azure_devops_url(
+            "ssh://git@ssh.dev.azure.com/v3/org/project/repo"
+        ));
+        assert!(!is_azure_devops_url(
+            "https://github.com/author/project.git"
+        ));
+        assert!(!is_azure_devops_url("https://notvisualstudio.com/repo"));
+    }
+
+    #[cfg(feature = "aws")]
+    #[test]
+    fn test_codecommit_region() {
+        assert_eq!(
+            codecommit_region("https://git-codecommit.us-east-2.amazonaws.com/v1/repos/project")
+                .as_deref(),
+            Some("us-east-2")
+        );
+        assert_eq!(
+            codecommit_region("https://git-codecommit.eu-west-1.amazonaws.com.cn/v1/repos/x"),
+            None
+        );
+        assert_eq!(
+            codecommit_region("https://github.com/author/project.git"),
+            None
+        );
+    }
+}
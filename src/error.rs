@@ -0,0 +1,52 @@
+use std::fmt;
+use std::io;
+use std::path::PathBuf;
+
+/// Errors that abort a clone run.
+#[derive(Debug)]
+pub enum RepoCloneError {
+    /// The git URL could not be split into domain, author and project.
+    InvalidUrl(String),
+    /// The target filesystem has less free space than was required.
+    InsufficientDiskSpace {
+        path: PathBuf,
+        available_mb: u64,
+        required_mb: u64,
+    },
+    /// `git verify-pack` found a pack whose contents don't match its index.
+    ChecksumMismatch(PathBuf),
+    Io(io::Error),
+}
+
+impl fmt::Display for RepoCloneError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RepoCloneError::InvalidUrl(url) => write!(f, "Failed to parse the git URL: {}", url),
+            RepoCloneError::InsufficientDiskSpace {
+                path,
+                available_mb,
+                required_mb,
+            } => write!(
+                f,
+                "Insufficient disk space at {}: {} MB available, {} MB required",
+                path.display(),
+                available_mb,
+                required_mb
+            ),
+            RepoCloneError::ChecksumMismatch(repo) => write!(
+                f,
+                "Pack checksum verification failed for {}",
+                repo.display()
+            ),
+            RepoCloneError::Io(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl std::error::Error for RepoCloneError {}
+
+impl From<io::Error> for RepoCloneError {
+    fn from(err: io::Error) -> Self {
+        RepoCloneError::Io(err)
+    }
+}
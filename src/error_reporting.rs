@@ -0,0 +1,49 @@
+use crate::error::RepoCloneError;
+use sentry::integrations::backtrace::current_stacktrace;
+use sentry::ClientInitGuard;
+
+/// Starts reporting to the Sentry project at `dsn`, including panics. Events
+/// are flushed when the returned guard is dropped.
+pub fn init(dsn: &str) -> ClientInitGuard {
+    let mut options = sentry::ClientOptions::new();
+    options.release = sentry::release_name!();
+    sentry::init((dsn, options))
+}
+
+/// Sends a failed run of `git_url` to Sentry. Does nothing unless `init`
+/// was called.
+pub fn report_failure(git_url: &str, err: &RepoCloneError) {
+    let mut event = sentry::event_from_error(err);
+    if let Some(exception) = event.exception.last_mut() {
+        exception.stacktrace = current_stacktrace();
+    }
+    event
+        .tags
+        .insert("git.url".to_string(), git_url.to_string());
+    sentry::capture_event(event);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_report_failure_captures_url_and_message() {
+        let err = RepoCloneError::ChecksumMismatch(PathBuf::from("/base/path/project"));
+
+        let events = sentry::test::with_captured_events(|| {
+            report_failure("https://github.com/author/project.git", &err);
+        });
+
+        assert_eq!(events.len(), 1);
+        let event = &events[0];
+        assert_eq!(
+            event.tags.get("git.url").map(String::as_str),
+            Some("https://github.com/author/project.git")
+        );
+        let exception = event.exception.last().unwrap();
+        assert_eq!(exception.value.as_deref(), Some(err.to_string().as_str()));
+        assert!(exception.stacktrace.is_some());
+    }
+}
@@ -0,0 +1,171 @@
+use serde::Serialize;
+use std::fmt;
+use std::io;
+
+/// Public GitHub REST API.
+pub const DEFAULT_API_BASE: &str = "https://api.github.com";
+
+/// The `state` of a GitHub commit status.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StatusState {
+    Success,
+    Failure,
+}
+
+impl fmt::Display for StatusState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StatusState::Success => write!(f, "success"),
+            StatusState::Failure => write!(f, "failure"),
+        }
+    }
+}
+
+/// A commit status to post for `repo` (`owner/name`) at `sha`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CommitStatus {
+    pub repo: String,
+    pub sha: String,
+    pub state: StatusState,
+    pub context: String,
+}
+
+/// Post a commit status for the cloned commit once the run finishes.
+#[derive(Clone, PartialEq)]
+pub struct StatusCheck {
+    pub token: String,
+    pub context: String,
+}
+
+/// Written by hand so the token never ends up in logs or panics.
+impl fmt::Debug for StatusCheck {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("StatusCheck")
+            .field("token", &"***")
+            .field("context", &self.context)
+            .finish()
+    }
+}
+
+/// Finds the commit `HEAD` points at in `git ls-remote` output.
+pub fn remote_head(ls_remote_output: &str) -> Option<&str> {
+    ls_remote_output.lines().find_map(|line| {
+        let (sha, name) = line.split_once('\t')?;
+        (name == "HEAD").then_some(sha)
+    })
+}
+
+#[derive(Serialize)]
+struct StatusRequest<'a> {
+    state: StatusState,
+    context: &'a str,
+    description: &'a str,
+}
+
+/// Posts commit statuses through the GitHub Statuses API.
+pub struct GitHubStatusClient {
+    api_base: String,
+}
+
+impl Default for GitHubStatusClient {
+    fn default() -> Self {
+        GitHubStatusClient {
+            api_base: DEFAULT_API_BASE.to_string(),
+        }
+    }
+}
+
+impl GitHubStatusClient {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sends requests to `api_base` instead of api.github.com.
+    pub fn with_api_base(mut self, api_base: &str) -> Self {
+        self.api_base = api_base.trim_end_matches('/').to_string();
+        self
+    }
+
+    pub fn post_status(
+        &self,
+        token: &str,
+        repo: &str,
+        sha: &str,
+        state: StatusState,
+        context: &str,
+    ) -> io::Result<()> {
+        let description = match state {
+            StatusState::Success => "Repository cloned successfully",
+            StatusState::Failure => "Repository clone failed",
+        };
+        ureq::post(&format!(
+            "{}/repos/{}/statuses/{}",
+            self.api_base, repo, sha
+        ))
+        .set("Authorization", &format!("Bearer {}", token))
+        .set("Accept", "application/vnd.github+json")
+        .set("User-Agent", "repo-cloner")
+        .send_json(StatusRequest {
+            state,
+            context,
+            description,
+        })
+        .map_err(|err| io::Error::other(format!("failed to post commit status: {}", err)))?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_http::serve_once;
+
+    #[test]
+    fn test_remote_head() {
+        let output = "1111\tHEAD\n2222\trefs/heads/main\n3333\trefs/tags/v1\n";
+        assert_eq!(remote_head(output), Some("1111"));
+        assert_eq!(remote_head("2222\trefs/heads/main\n"), None);
+    }
+
+    #[test]
+    fn test_status_check_debug_hides_the_token() {
+        let check = StatusCheck {
+            token: "ghp_secret".to_string(),
+            context: "repo-cloner".to_string(),
+        };
+        assert_eq!(
+            format!("{:?}", check),
+            r#"StatusCheck { token: "***", context: "repo-cloner" }"#
+        );
+    }
+
+    #[test]
+    fn test_post_status_calls_statuses_api() {
+        let (base, server) = serve_once("201 Created", "{}");
+
+        GitHubStatusClient::new()
+            .with_api_base(&base)
+            .post_status(
+                "secret-token",
+                "author/project",
+                "0123abcd",
+                StatusState::Success,
+                "repo-cloner",
+            )
+            .unwrap();
+
+        let request = server.join().unwrap();
+        assert_eq!(
+            request.line,
+            "POST /repos/author/project/statuses/0123abcd HTTP/1.1"
+        );
+        assert!(request
+            .headers
+            .iter()
+            .any(|header| header == "Authorization: Bearer secret-token"));
+        let body: serde_json::Value = serde_json::from_str(&request.body).unwrap();
+        assert_eq!(body["state"], "success");
+        assert_eq!(body["context"], "repo-cloner");
+    }
+}
@@ -0,0 +1,121 @@
+use url::Url;
+
+/// A `[submodule "<name>"]` entry from a `.gitmodules` file.
+#[derive(Debug, PartialEq)]
+pub struct Submodule {
+    pub name: String,
+    pub path: String,
+    pub url: String,
+}
+
+/// Parses the contents of a `.gitmodules` file, skipping entries without a `url`.
+pub fn parse_gitmodules(contents: &str) -> Vec<Submodule> {
+    let mut submodules = Vec::new();
+    let mut current: Option<(String, Option<String>, Option<String>)> = None;
+
+    for line in contents.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') || line.starts_with(';') {
+            continue;
+        }
+
+        if line.starts_with('[') {
+            submodules.extend(current.take().and_then(into_submodule));
+            current = line
+                .strip_prefix("[submodule")
+                .and_then(|rest| rest.trim().strip_suffix(']'))
+                .map(|name| (name.trim().trim_matches('"').to_string(), None, None));
+            continue;
+        }
+
+        if let (Some((_, path, url)), Some((key, value))) = (current.as_mut(), line.split_once('='))
+        {
+            let value = value.trim().trim_matches('"').to_string();
+            match key.trim() {
+                "path" => *path = Some(value),
+                "url" => *url = Some(value),
+                _ => {}
+            }
+        }
+    }
+    submodules.extend(current.and_then(into_submodule));
+    submodules
+}
+
+fn into_submodule(
+    (name, path, url): (String, Option<String>, Option<String>),
+) -> Option<Submodule> {
+    let url = url?;
+    let path = path.unwrap_or_else(|| name.clone());
+    Some(Submodule { name, path, url })
+}
+
+/// Resolves a submodule URL that may be relative (`./` or `../`) to the
+/// superproject's URL, the same way `git submodule init` does.
+pub fn resolve_submodule_url(superproject_url: &str, url: &str) -> String {
+    if !(url.starts_with("./") || url.starts_with("../")) {
+        return url.to_string();
+    }
+    // Git treats the superproject URL as a directory, so `../sibling.git`
+    // replaces the superproject's last path segment.
+    let base = format!("{}/", superproject_url.trim_end_matches('/'));
+    Url::parse(&base)
+        .and_then(|base| base.join(url))
+        .map(|resolved| resolved.to_string())
+        .unwrap_or_else(|_| url.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_gitmodules() {
+        let contents = r#"
+# vendored dependencies
+[submodule "libs/foo"]
+	path = libs/foo
+	url = https://github.com/author/foo.git
+[core]
+	url = https://example.com/ignored.git
+[submodule "bar"]
+	url = ../bar.git
+	branch = main
+[submodule "no-url"]
+	path = no-url
+"#;
+
+        assert_eq!(
+            parse_gitmodules(contents),
+            vec![
+                Submodule {
+                    name: "libs/foo".to_string(),
+                    path: "libs/foo".to_string(),
+                    url: "https://github.com/author/foo.git".to_string(),
+                },
+                Submodule {
+                    name: "bar".to_string(),
+                    path: "bar".to_string(),
+                    url: "../bar.git".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_resolve_submodule_url() {
+        let parent = "https://github.com/author/project.git";
+        assert_eq!(
+            resolve_submodule_url(parent, "../sibling.git"),
+            "https://github.com/author/sibling.git"
+        );
+        assert_eq!(
+            resolve_submodule_url(parent, "../../other/lib.git"),
+            "https://github.com/other/lib.git"
+        );
+        assert_eq!(
+            resolve_submodule_url(parent, "https://gitlab.com/a/b.git"),
+            "https://gitlab.com/a/b.git"
+        );
+    }
+}
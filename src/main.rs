@@ -1,10 +1,36 @@
-use clap::Parser;
+use clap::builder::NonEmptyStringValueParser;
+use clap::error::ErrorKind;
+use clap::{CommandFactory, Parser};
+#[cfg(feature = "vault")]
+use repo_cloner::askpass;
+use repo_cloner::credential_helpers;
+use repo_cloner::credential_prompt::{self, CredentialPrompt};
+#[cfg(feature = "sentry")]
+use repo_cloner::error_reporting;
+use repo_cloner::github::StatusCheck;
+use repo_cloner::naming::{check_timestamp_format, CaseConverter, DEFAULT_TIMESTAMP_FORMAT};
+use repo_cloner::output::OutputFormat;
+use repo_cloner::remotes::parse_remotes;
+use repo_cloner::sparse::{merge_patterns, parse_sparse_patterns};
+#[cfg(feature = "statsd")]
+use repo_cloner::statsd::StatsdReporter;
+#[cfg(unix)]
+use repo_cloner::tee::StderrTee;
+use repo_cloner::tee::TeeWriter;
+#[cfg(feature = "opentelemetry")]
+use repo_cloner::telemetry;
+#[cfg(feature = "vault")]
+use repo_cloner::vault::{Credentials, VaultClient};
+#[cfg(any(feature = "opentelemetry", feature = "statsd"))]
+use repo_cloner::RepoCommands;
+use repo_cloner::{
+    CloneOptions, ClonerOptions, DryRunRepoCommands, RepoCloner, SystemRepoCommands,
+};
 use std::env;
-use std::fs;
-use std::io;
-use std::path::Path;
-use std::path::PathBuf;
-use std::process::Command;
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process;
 use url::Url;
 
 /// A simple CLI tool to clone git repositories to a specific directory structure.
@@ -21,249 +47,909 @@ struct Args {
     /// Perform a dry run (print the commands without executing them)
     #[arg(long)]
     dry_run: bool,
+
+    /// Abort before cloning if less than this many MB are free on the target filesystem
+    #[arg(long, value_name = "MB")]
+    check_disk_space: Option<u64>,
+
+    /// Clone each submodule into its own structured location and link it back to the superproject
+    #[arg(long)]
+    submodules_structured: bool,
+
+    /// Change the casing of the project directory name
+    #[arg(long, value_enum, value_name = "CASE")]
+    project_name_case: Option<CaseConverter>,
+
+    /// Warn before cloning if the remote advertises more than --large-clone-threshold refs
+    #[arg(long)]
+    warn_on_large_clone: bool,
+
+    /// Ref count above which --warn-on-large-clone warns
+    #[arg(
+        long,
+        value_name = "REFS",
+        default_value_t = 1000,
+        requires = "warn_on_large_clone"
+    )]
+    large_clone_threshold: usize,
+
+    /// Strip a trailing `.git` from the project directory name (default)
+    #[arg(long, overrides_with = "no_clean_git_suffix_in_path")]
+    clean_git_suffix_in_path: bool,
+
+    /// Keep a trailing `.git` in the project directory name
+    #[arg(long, overrides_with = "clean_git_suffix_in_path")]
+    no_clean_git_suffix_in_path: bool,
+
+    /// Add remotes after cloning: an SSH URL (added as `ssh`) or `name=url,name=url` pairs
+    #[arg(long, value_name = "REMOTES")]
+    multi_origin: Option<String>,
+
+    /// Create a bundle of all refs at this path after cloning
+    #[arg(long, value_name = "PATH")]
+    bundle_output: Option<PathBuf>,
+
+    /// Send a protocol v2 server option to the remote (repeatable)
+    #[arg(long, value_name = "OPTION")]
+    server_option: Vec<String>,
+
+    /// Path to the upload-pack program to run on the remote
+    #[arg(long, value_name = "PATH", value_parser = NonEmptyStringValueParser::new())]
+    upload_pack: Option<String>,
+
+    /// Disable git's local-clone optimizations (hardlinks) for local sources
+    #[arg(long)]
+    no_local: bool,
+
+    /// Path to the receive-pack program `git push` should run on the remote
+    #[arg(long, value_name = "PATH", value_parser = NonEmptyStringValueParser::new())]
+    receive_pack: Option<String>,
+
+    /// Tag the cloned HEAD with `cloned-at-<timestamp>`
+    #[arg(long)]
+    auto_tag_clone_date: bool,
+
+    /// Run `pre-commit install` after cloning if the repo has a .pre-commit-config.yaml
+    #[arg(long)]
+    init_pre_commit: bool,
+
+    /// Copy the hooks in this directory into the cloned repo's .git/hooks
+    #[arg(long, value_name = "HOOKS_DIR")]
+    init_git_hooks: Option<PathBuf>,
+
+    /// Block pushes from local branches matching this glob after cloning (repeatable)
+    #[arg(long, value_name = "PATTERN")]
+    protect_branch: Vec<String>,
+
+    /// Add the clone to git's global safe.directory list (useful in CI running as root)
+    #[arg(long)]
+    set_safe_directory: bool,
+
+    /// Print one JSON object per line for each completed clone instead of text messages
+    #[arg(long, alias = "ndjson", conflicts_with_all = ["csv_output", "tsv_output"])]
+    json_lines: bool,
+
+    /// Print a CSV header and one row per completed clone instead of text messages
+    #[arg(long, conflicts_with = "tsv_output")]
+    csv_output: bool,
+
+    /// Like --csv-output, but TAB-separated and unquoted for awk/cut
+    #[arg(long)]
+    tsv_output: bool,
+
+    /// Append the clone time to the project directory name, e.g. `project-20240101T120000Z`
+    #[arg(long)]
+    append_timestamp_to_path: bool,
+
+    /// strftime format for --append-timestamp-to-path
+    #[arg(
+        long,
+        value_name = "STRFTIME",
+        requires = "append_timestamp_to_path",
+        default_value = DEFAULT_TIMESTAMP_FORMAT,
+        value_parser = timestamp_format
+    )]
+    timestamp_format: String,
+
+    /// After cloning, delete all but the N most recent timestamped copies of the project
+    #[arg(
+        long,
+        value_name = "N",
+        requires = "append_timestamp_to_path",
+        value_parser = clap::value_parser!(u64).range(1..)
+    )]
+    keep_n_copies: Option<u64>,
+
+    /// After cloning, check every pack file against its index with `git verify-pack`
+    #[arg(long)]
+    verify_checksums: bool,
+
+    /// Run `git gc --aggressive` after cloning (slow, but smallest on disk)
+    #[arg(long)]
+    gc_aggressive: bool,
+
+    /// Repack all objects with this delta depth after cloning (`git repack -a -d --depth=N`)
+    #[arg(long, value_name = "N")]
+    repack: Option<u32>,
+
+    /// After cloning, let the repo borrow objects from this repository's .git/objects
+    #[arg(long, value_name = "PATH")]
+    add_alternate: Option<PathBuf>,
+
+    /// Copy this file to the clone's info/attributes before the working tree is checked out
+    #[arg(long, value_name = "PATH")]
+    git_attributes: Option<PathBuf>,
+
+    /// With --submodules-structured, warn and continue when a submodule can't be cloned or updated
+    #[arg(long, requires = "submodules_structured")]
+    ignore_submodule_errors: bool,
+
+    /// After cloning, write `export REPO_URL=...` style lines for the clone to this file
+    #[arg(long, value_name = "PATH")]
+    export_env: Option<PathBuf>,
+
+    /// Also write everything repo-cloner and git print to stdout and stderr to this file
+    #[arg(long, value_name = "PATH")]
+    tee_output: Option<PathBuf>,
+
+    /// Directory to run git and other spawned commands in (defaults to the current directory)
+    #[arg(long, value_name = "PATH")]
+    workdir: Option<PathBuf>,
+
+    /// Store the repository at this path instead of in the clone's `.git` (`--separate-git-dir`)
+    #[arg(long, value_name = "PATH")]
+    git_dir: Option<PathBuf>,
+
+    /// Run git with core.fsmonitor disabled so it never starts a filesystem monitor daemon
+    #[arg(long)]
+    disable_fsmonitor: bool,
+
+    /// Run git with core.hooksPath=/dev/null so no hooks fire during the clone and setup
+    #[arg(long)]
+    skip_hooks: bool,
+
+    /// Pre-populate the object store from a bundle at this http(s):// or file:// URI (git 2.40+)
+    #[arg(long, value_name = "URI", value_parser = bundle_uri)]
+    bundle_uri: Option<String>,
+
+    /// Only check out files matching this gitignore-style pattern (repeatable)
+    #[arg(long, value_name = "PATTERN")]
+    sparse: Vec<String>,
+
+    /// Read additional --sparse patterns from this file, one per line (# starts a comment)
+    #[arg(long, value_name = "PATH")]
+    sparse_checkout_file: Option<PathBuf>,
+
+    /// Post a GitHub commit status (success/failure) for the cloned commit using this token
+    #[arg(long, value_name = "TOKEN")]
+    github_status_check: Option<String>,
+
+    /// Context name for --github-status-check
+    #[arg(
+        long,
+        value_name = "CONTEXT",
+        requires = "github_status_check",
+        default_value = "repo-cloner"
+    )]
+    status_context: String,
+
+    /// Export a span per clone to this OpenTelemetry collector (OTLP over HTTP)
+    #[cfg(feature = "opentelemetry")]
+    #[arg(long, value_name = "URL")]
+    opentelemetry_endpoint: Option<String>,
+
+    /// Send per-clone timing and success/failure metrics to this StatsD server
+    #[cfg(feature = "statsd")]
+    #[arg(long, value_name = "HOST:PORT")]
+    statsd: Option<String>,
+
+    /// Report failed clones and panics to this Sentry DSN
+    #[cfg(feature = "sentry")]
+    #[arg(long, value_name = "DSN")]
+    sentry_dsn: Option<String>,
+
+    /// Fetch git credentials from the HashiCorp Vault server at this URL
+    #[cfg(feature = "vault")]
+    #[arg(long, value_name = "URL", requires = "vault_path")]
+    vault_addr: Option<String>,
+
+    /// KV v2 secret holding `username` and `password`, as <mount>/<secret>
+    #[cfg(feature = "vault")]
+    #[arg(long, value_name = "SECRET_PATH", requires = "vault_addr")]
+    vault_path: Option<String>,
+
+    /// Vault token (defaults to $VAULT_TOKEN)
+    #[cfg(feature = "vault")]
+    #[arg(long, value_name = "TOKEN", requires = "vault_addr")]
+    vault_token: Option<String>,
+
+    /// Authenticate to AWS CodeCommit with `aws codecommit credential-helper`
+    #[cfg(feature = "aws")]
+    #[arg(long)]
+    aws_codecommit_credential_helper: bool,
+
+    /// Authenticate to Google Cloud Source Repositories with gcloud's credential helper
+    #[arg(long)]
+    gcloud_credential_helper: bool,
+
+    /// Authenticate to Azure DevOps with Git Credential Manager
+    #[arg(long)]
+    azure_credential_helper: bool,
+
+    /// Prompt for a username and password and clone with them in the HTTP(S) URL
+    #[arg(long)]
+    interactive_credentials: bool,
+
+    /// Answer git's credential prompts with this program (sets GIT_ASKPASS)
+    #[arg(long, value_name = "PATH", value_parser = askpass_script)]
+    git_askpass_script: Option<PathBuf>,
+
+    /// Limit origin's fetch refspec to this refspec after cloning (repeatable)
+    #[arg(long, value_name = "REFSPEC", value_parser = mirror_filter)]
+    mirror_filter: Vec<String>,
+
+    /// Keep a bare clone of each URL in this directory and clone with `--reference` to it
+    #[arg(long, value_name = "PATH")]
+    cache_dir: Option<PathBuf>,
+
+    /// Spawn every command through `sh -c "<command line>"` instead of directly
+    #[arg(long)]
+    run_in_shell: bool,
 }
 
 fn main() {
     let args = Args::parse();
-    let base_path = args.base_path.unwrap_or_else(|| {
+    #[cfg(feature = "sentry")]
+    let sentry_guard = args.sentry_dsn.as_deref().map(error_reporting::init);
+    let base_path = args.base_path.clone().unwrap_or_else(|| {
         env::current_dir()
             .expect("Failed to get current directory")
             .to_string_lossy()
             .to_string()
     });
-
-    if args.dry_run {
-        let cloner = RepoCloner::new(DryRunRepoCommands);
-        cloner.run(&args.git_url, &base_path);
+    // Paths handed to git would otherwise be resolved against --workdir.
+    let base_path = match &args.workdir {
+        Some(_) => std::path::absolute(&base_path)
+            .expect("Failed to resolve the base path")
+            .to_string_lossy()
+            .to_string(),
+        None => base_path,
+    };
+    let mut options = cloner_options(&args);
+    // A dry run never contacts the remote, so there is nothing to ask for.
+    if args.interactive_credentials && !args.dry_run {
+        options.clone.credentials = Some(prompt_credentials(&args.git_url));
+    }
+    let tee_file = args.tee_output.as_ref().map(|path| {
+        File::create(path).unwrap_or_else(|err| {
+            eprintln!("Error: failed to create {}: {}", path.display(), err);
+            process::exit(1);
+        })
+    });
+    #[cfg(unix)]
+    let stderr_tee = tee_file.as_ref().map(|file| {
+        file.try_clone()
+            .and_then(StderrTee::start)
+            .unwrap_or_else(|err| {
+                eprintln!("Error: failed to copy stderr to --tee-output: {}", err);
+                process::exit(1);
+            })
+    });
+    // Dry runs clone nothing, so they have nothing to trace.
+    #[cfg(feature = "opentelemetry")]
+    let tracer_provider = args
+        .opentelemetry_endpoint
+        .as_deref()
+        .filter(|_| !args.dry_run)
+        .map(|endpoint| {
+            telemetry::otlp_tracer_provider(endpoint).unwrap_or_else(|err| {
+                eprintln!("Error: failed to set up OpenTelemetry export: {}", err);
+                process::exit(1);
+            })
+        });
+
+    let result = if args.dry_run {
+        let commands = dry_run_commands(&args).with_output(messages(&args, &tee_file));
+        let cloner = RepoCloner::new(commands)
+            .with_options(options)
+            .with_output(stdout(&tee_file));
+        cloner.run(&args.git_url, &base_path)
     } else {
-        let cloner = RepoCloner::new(SystemRepoCommands);
-        cloner.run(&args.git_url, &base_path);
+        let commands = system_commands(&args).with_output(messages(&args, &tee_file));
+        #[cfg(feature = "vault")]
+        let (commands, askpass_script) = vault_credentials(commands, &args);
+        let cloner = RepoCloner::new(commands)
+            .with_options(options)
+            .with_output(stdout(&tee_file));
+        #[cfg(feature = "opentelemetry")]
+        let cloner = with_tracer(cloner, &tracer_provider);
+        #[cfg(feature = "statsd")]
+        let cloner = with_statsd(cloner, &args);
+        let result = cloner.run(&args.git_url, &base_path);
+        #[cfg(feature = "vault")]
+        if let Some(script) = askpass_script {
+            let _ = fs::remove_file(script);
+        }
+        result
     };
-}
 
-trait RepoCommands {
-    fn git_clone(&self, url: &str, clone_path: &Path);
-    fn cd_destination(&self, clone_path: &Path);
-    fn display_success(&self);
-    fn create_dir_all(&self, path: &Path) -> io::Result<()>;
-}
+    #[cfg(feature = "opentelemetry")]
+    if let Some(provider) = tracer_provider {
+        if let Err(err) = provider.shutdown() {
+            eprintln!("Warning: failed to export spans: {}", err);
+        }
+    }
 
-struct SystemRepoCommands;
+    // process::exit skips destructors, so flush pending events first.
+    #[cfg(feature = "sentry")]
+    drop(sentry_guard);
 
-impl RepoCommands for SystemRepoCommands {
-    fn git_clone(&self, url: &str, clone_path: &Path) {
-        Command::new("git")
-            .arg("clone")
-            .arg(url)
-            .arg(clone_path)
-            .status()
-            .expect("Failed to clone repository");
+    if let Err(err) = &result {
+        eprintln!("Error: {}", err);
+    }
+    // Let the --tee-output copy of stderr catch up before exiting.
+    #[cfg(unix)]
+    drop(stderr_tee);
+    if result.is_err() {
+        process::exit(1);
     }
+}
 
-    fn cd_destination(&self, clone_path: &Path) {
-        println!("cd {}", clone_path.to_string_lossy());
+fn cloner_options(args: &Args) -> ClonerOptions {
+    ClonerOptions {
+        min_free_space_mb: args.check_disk_space,
+        structured_submodules: args.submodules_structured,
+        project_name_case: args.project_name_case,
+        large_clone_ref_threshold: args
+            .warn_on_large_clone
+            .then_some(args.large_clone_threshold),
+        keep_git_suffix: args.no_clean_git_suffix_in_path,
+        remotes: args
+            .multi_origin
+            .as_deref()
+            .map(|value| parse_remotes(value).unwrap_or_else(|err| usage_error(&err)))
+            .unwrap_or_default(),
+        bundle_output: args.bundle_output.clone(),
+        clone: CloneOptions {
+            server_options: args.server_option.clone(),
+            upload_pack: args.upload_pack.clone(),
+            no_local: args.no_local,
+            // Resolved here since git may run in a different --workdir.
+            separate_git_dir: args.git_dir.as_ref().map(|path| {
+                std::path::absolute(path).unwrap_or_else(|err| usage_error(&err.to_string()))
+            }),
+            bundle_uri: args.bundle_uri.clone(),
+            mirror_filters: args.mirror_filter.clone(),
+            ..Default::default()
+        },
+        receive_pack: args.receive_pack.clone(),
+        tag_clone_date: args.auto_tag_clone_date,
+        init_pre_commit: args.init_pre_commit,
+        hooks_dir: args.init_git_hooks.clone(),
+        protected_branches: args.protect_branch.clone(),
+        set_safe_directory: args.set_safe_directory,
+        output_format: output_format(args),
+        path_timestamp_format: args
+            .append_timestamp_to_path
+            .then(|| args.timestamp_format.clone()),
+        keep_copies: args.keep_n_copies.map(|n| n as usize),
+        verify_checksums: args.verify_checksums,
+        gc_aggressive: args.gc_aggressive,
+        repack_depth: args.repack,
+        alternate: args.add_alternate.clone(),
+        gitattributes: args.git_attributes.clone(),
+        ignore_submodule_errors: args.ignore_submodule_errors,
+        export_env: args.export_env.clone(),
+        sparse_patterns: sparse_patterns(args),
+        status_check: args.github_status_check.clone().map(|token| StatusCheck {
+            token,
+            context: args.status_context.clone(),
+        }),
+        cache_dir: args.cache_dir.clone(),
     }
+}
 
-    fn display_success(&self) {
-        println!("Repository cloned successfully.");
+fn timestamp_format(value: &str) -> Result<String, String> {
+    check_timestamp_format(value).map(|()| value.to_string())
+}
+
+#[cfg(feature = "opentelemetry")]
+fn with_tracer<C: RepoCommands>(
+    cloner: RepoCloner<C>,
+    provider: &Option<opentelemetry_sdk::trace::SdkTracerProvider>,
+) -> RepoCloner<C> {
+    match provider {
+        Some(provider) => cloner.with_tracer(telemetry::tracer(provider)),
+        None => cloner,
     }
+}
 
-    fn create_dir_all(&self, path: &Path) -> io::Result<()> {
-        fs::create_dir_all(path)
+#[cfg(feature = "statsd")]
+fn with_statsd<C: RepoCommands>(cloner: RepoCloner<C>, args: &Args) -> RepoCloner<C> {
+    match &args.statsd {
+        Some(addr) => {
+            cloner.with_statsd(StatsdReporter::new(addr.as_str()).unwrap_or_else(|err| {
+                eprintln!("Error: invalid StatsD address {}: {}", addr, err);
+                process::exit(1);
+            }))
+        }
+        None => cloner,
     }
 }
 
-struct DryRunRepoCommands;
+/// Reads `--vault-path` from Vault and has git answer credential prompts
+/// with it, returning the askpass script to remove once git is done.
+#[cfg(feature = "vault")]
+fn vault_credentials(
+    commands: SystemRepoCommands,
+    args: &Args,
+) -> (SystemRepoCommands, Option<PathBuf>) {
+    let (Some(addr), Some(path)) = (&args.vault_addr, &args.vault_path) else {
+        return (commands, None);
+    };
+    let token = args
+        .vault_token
+        .clone()
+        .or_else(|| env::var("VAULT_TOKEN").ok())
+        .unwrap_or_else(|| usage_error("--vault-addr needs --vault-token or VAULT_TOKEN"));
+    let credentials = VaultClient::new(addr, &token)
+        .read_credentials(path)
+        .unwrap_or_else(|err| {
+            eprintln!("Error: {}", err);
+            process::exit(1);
+        });
+    let script = askpass::install_script(&env::temp_dir()).unwrap_or_else(|err| {
+        eprintln!("Error: failed to write the askpass script: {}", err);
+        process::exit(1);
+    });
+    let commands = with_credentials(commands, &script, &credentials);
+    (commands, Some(script))
+}
+
+#[cfg(feature = "vault")]
+fn with_credentials(
+    commands: SystemRepoCommands,
+    script: &Path,
+    credentials: &Credentials,
+) -> SystemRepoCommands {
+    askpass::credential_env(script, &credentials.username, &credentials.password)
+        .into_iter()
+        .fold(commands, |commands, (key, value)| {
+            commands.with_env(key, value)
+        })
+}
 
-impl RepoCommands for DryRunRepoCommands {
-    fn git_clone(&self, url: &str, clone_path: &Path) {
-        println!("DRY RUN: git clone {} {}", url, clone_path.display());
+fn prompt_credentials(git_url: &str) -> (String, String) {
+    if credential_prompt::url_with_credentials(git_url, "", "").is_none() {
+        usage_error("--interactive-credentials needs an http:// or https:// URL");
     }
+    CredentialPrompt::prompt_terminal().unwrap_or_else(|err| {
+        eprintln!("Error: failed to read credentials: {}", err);
+        process::exit(1);
+    })
+}
+
+/// The `-c` overrides and environment variables flags add to every git
+/// command, shared by real and dry runs so both show the same commands.
+#[derive(Default)]
+struct GitSettings {
+    config: Vec<(&'static str, String)>,
+    env: Vec<(&'static str, String)>,
+}
 
-    fn cd_destination(&self, clone_path: &Path) {
-        println!("DRY RUN: cd {}", clone_path.display());
+fn system_commands(args: &Args) -> SystemRepoCommands {
+    let settings = git_settings(args);
+    let mut commands = SystemRepoCommands::new()
+        .with_run_in_shell(args.run_in_shell)
+        .with_working_dir(args.workdir.clone());
+    for (key, value) in settings.config {
+        commands = commands.with_config(key, value);
     }
+    for (key, value) in settings.env {
+        commands = commands.with_env(key, value);
+    }
+    commands
+}
 
-    fn display_success(&self) {
-        println!("DRY RUN: Repository cloned successfully.");
+fn dry_run_commands(args: &Args) -> DryRunRepoCommands {
+    let settings = git_settings(args);
+    let mut commands = DryRunRepoCommands::new().with_run_in_shell(args.run_in_shell);
+    for (key, value) in settings.config {
+        commands = commands.with_config(key, value);
     }
+    for (key, value) in settings.env {
+        commands = commands.with_env(key, value);
+    }
+    commands
+}
 
-    fn create_dir_all(&self, path: &Path) -> io::Result<()> {
-        println!("DRY RUN: mkdir -p {}", path.display());
-        Ok(())
+fn git_settings(args: &Args) -> GitSettings {
+    let mut settings = GitSettings::default();
+    if args.disable_fsmonitor {
+        settings
+            .config
+            .push(("core.useBuiltinFSMonitor", "false".to_string()));
+        settings
+            .config
+            .push(("core.fsmonitor", "false".to_string()));
+    }
+    if args.skip_hooks {
+        settings
+            .config
+            .push(("core.hooksPath", "/dev/null".to_string()));
+    }
+    if let Some(script) = &args.git_askpass_script {
+        settings
+            .env
+            .push(("GIT_ASKPASS", script.to_string_lossy().into_owned()));
     }
+    if args.gcloud_credential_helper {
+        // The Cloud SDK ships the helper as git-credential-gcloud.sh.
+        settings
+            .config
+            .push(("credential.helper", "gcloud.sh".to_string()));
+    }
+    if args.azure_credential_helper {
+        if credential_helpers::is_azure_devops_url(&args.git_url) {
+            settings
+                .config
+                .push(("credential.helper", "manager".to_string()));
+        } else {
+            eprintln!(
+                "Warning: {} is not an Azure DevOps URL; ignoring --azure-credential-helper.",
+                args.git_url
+            );
+        }
+    }
+    #[cfg(feature = "aws")]
+    if args.aws_codecommit_credential_helper {
+        settings.config.push((
+            "credential.helper",
+            "!aws codecommit credential-helper $@".to_string(),
+        ));
+        settings
+            .config
+            .push(("credential.UseHttpPath", "true".to_string()));
+        if let Some(region) = credential_helpers::codecommit_region(&args.git_url) {
+            settings.env.push(("AWS_DEFAULT_REGION", region));
+        }
+    }
+    settings
 }
 
-struct RepoCloner<C: RepoCommands> {
-    commands: C,
+/// Stdout, duplicated into the --tee-output file when one is given.
+fn stdout(tee_file: &Option<File>) -> Box<dyn Write> {
+    match tee_file {
+        Some(file) => Box::new(TeeWriter::new(
+            io::stdout(),
+            file.try_clone()
+                .expect("failed to duplicate the --tee-output file handle"),
+        )),
+        None => Box::new(io::stdout()),
+    }
 }
 
-impl<C: RepoCommands> RepoCloner<C> {
-    fn new(commands: C) -> Self {
-        RepoCloner { commands }
+/// Where the `cd …` and success messages go: stderr when --export-env
+/// writes to stdout, so `source <(repo-cloner --export-env /dev/stdout …)`
+/// only sees the export lines.
+fn messages(args: &Args, tee_file: &Option<File>) -> Box<dyn Write> {
+    if args.export_env.as_deref().is_some_and(is_stdout) {
+        Box::new(io::stderr())
+    } else {
+        stdout(tee_file)
     }
+}
 
-    fn run(&self, git_url: &str, base_path: &str) {
-        if let Some((domain, author, project)) = self.parse_git_url(git_url) {
-            let clone_dir = self.create_directory_structure(base_path, &domain, &author);
-            let project_path = clone_dir.join(project);
+fn is_stdout(path: &Path) -> bool {
+    ["/dev/stdout", "/dev/fd/1", "/proc/self/fd/1"]
+        .iter()
+        .any(|stdout| path == Path::new(stdout))
+}
 
-            self.commands.git_clone(git_url, &project_path);
-            self.commands.cd_destination(&project_path);
-            self.commands.display_success();
-        } else {
-            eprintln!("Failed to parse the git URL.");
+/// The --sparse patterns followed by those from --sparse-checkout-file.
+fn sparse_patterns(args: &Args) -> Vec<String> {
+    let from_file = args
+        .sparse_checkout_file
+        .as_ref()
+        .map(|path| match fs::read_to_string(path) {
+            Ok(contents) => parse_sparse_patterns(&contents),
+            Err(err) => usage_error(&format!("failed to read {}: {}", path.display(), err)),
+        })
+        .unwrap_or_default();
+    merge_patterns(args.sparse.clone(), from_file)
+}
+
+fn bundle_uri(value: &str) -> Result<String, String> {
+    match Url::parse(value) {
+        Ok(uri) if matches!(uri.scheme(), "http" | "https" | "file") => Ok(value.to_string()),
+        Ok(uri) => Err(format!(
+            "unsupported scheme '{}', expected http, https or file",
+            uri.scheme()
+        )),
+        Err(err) => Err(format!("invalid URI: {}", err)),
+    }
+}
+
+/// Accepts an existing executable, made absolute since git may run in
+/// another --workdir.
+fn askpass_script(value: &str) -> Result<PathBuf, String> {
+    let path = std::path::absolute(value).map_err(|err| err.to_string())?;
+    let metadata = fs::metadata(&path).map_err(|err| format!("{}: {}", path.display(), err))?;
+    if !metadata.is_file() {
+        return Err(format!("{} is not a file", path.display()));
+    }
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        if metadata.permissions().mode() & 0o111 == 0 {
+            return Err(format!("{} is not executable", path.display()));
         }
     }
+    Ok(path)
+}
 
-    fn parse_git_url(&self, git_url: &str) -> Option<(String, String, String)> {
-        let parsed_url = Url::parse(git_url).ok()?;
-        let domain = parsed_url.host_str()?.to_string();
-        let mut path_segments = parsed_url.path_segments()?;
-        let author = path_segments.next()?.to_string();
-        let project = path_segments.next()?.to_string().replace(".git", "");
-        Some((domain, author, project))
+fn mirror_filter(value: &str) -> Result<String, String> {
+    match value.trim_start_matches('+').split_once(':') {
+        Some((src, dst)) if !src.is_empty() && !dst.is_empty() => Ok(value.to_string()),
+        _ => Err("expected a refspec like +refs/heads/main:refs/heads/main".to_string()),
     }
+}
 
-    fn create_directory_structure(&self, base_path: &str, domain: &str, author: &str) -> PathBuf {
-        let path = PathBuf::from(base_path).join(domain).join(author);
-        self.commands
-            .create_dir_all(&path)
-            .expect("Failed to create directories");
-        path
+fn output_format(args: &Args) -> OutputFormat {
+    if args.json_lines {
+        OutputFormat::JsonLines
+    } else if args.csv_output {
+        OutputFormat::Csv
+    } else if args.tsv_output {
+        OutputFormat::Tsv
+    } else {
+        OutputFormat::Text
     }
 }
 
+/// Exits with a clap-formatted usage error.
+fn usage_error(message: &str) -> ! {
+    Args::command()
+        .error(ErrorKind::ValueValidation, message)
+        .exit()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::cell::RefCell;
 
-    struct MockRepoCommands {
-        pub cloned_repos: RefCell<Vec<(String, PathBuf)>>,
-        pub navigated_paths: RefCell<Vec<PathBuf>>,
-        pub success: RefCell<bool>,
-        pub created_paths: RefCell<Vec<PathBuf>>,
+    fn parse(extra: &[&str]) -> Args {
+        let mut argv = vec!["repo-cloner", "https://github.com/author/project.git"];
+        argv.extend_from_slice(extra);
+        Args::try_parse_from(argv).unwrap()
     }
 
-    impl RepoCommands for MockRepoCommands {
-        fn git_clone(&self, url: &str, clone_path: &Path) {
-            self.cloned_repos
-                .borrow_mut()
-                .push((url.to_string(), clone_path.to_path_buf()));
-        }
-
-        fn cd_destination(&self, clone_path: &Path) {
-            self.navigated_paths
-                .borrow_mut()
-                .push(clone_path.to_path_buf());
-        }
-
-        fn display_success(&self) {
-            self.success.replace_with(|_| true);
-        }
+    #[test]
+    fn test_ndjson_is_an_alias_for_json_lines() {
+        let json_lines = parse(&["--json-lines"]);
+        let ndjson = parse(&["--ndjson"]);
+
+        assert!(ndjson.json_lines);
+        assert_eq!(output_format(&ndjson), OutputFormat::JsonLines);
+        assert_eq!(output_format(&ndjson), output_format(&json_lines));
+        assert!(Args::try_parse_from([
+            "repo-cloner",
+            "https://github.com/author/project.git",
+            "--ndjson",
+            "--csv-output",
+        ])
+        .is_err());
+    }
 
-        fn create_dir_all(&self, path: &Path) -> io::Result<()> {
-            self.created_paths.borrow_mut().push(path.to_path_buf());
-            Ok(())
-        }
+    #[test]
+    fn test_warn_on_large_clone_before_the_url() {
+        let args = Args::try_parse_from([
+            "repo-cloner",
+            "--warn-on-large-clone",
+            "https://github.com/author/project.git",
+        ])
+        .unwrap();
+        assert_eq!(args.git_url, "https://github.com/author/project.git");
+        assert_eq!(cloner_options(&args).large_clone_ref_threshold, Some(1000));
+
+        let args = parse(&["--warn-on-large-clone", "--large-clone-threshold", "50"]);
+        assert_eq!(cloner_options(&args).large_clone_ref_threshold, Some(50));
+        assert_eq!(cloner_options(&parse(&[])).large_clone_ref_threshold, None);
     }
 
-    impl MockRepoCommands {
-        pub fn new() -> Self {
-            Self {
-                cloned_repos: RefCell::new(vec![]),
-                navigated_paths: RefCell::new(vec![]),
-                success: RefCell::new(false),
-                created_paths: RefCell::new(vec![]),
-            }
-        }
+    #[test]
+    fn test_export_env_to_stdout() {
+        assert!(is_stdout(Path::new("/dev/stdout")));
+        assert!(is_stdout(Path::new("/proc/self/fd/1")));
+        assert!(!is_stdout(Path::new("repo.env")));
+        assert!(!is_stdout(Path::new("/dev/stderr")));
     }
 
     #[test]
-    fn test_clone_repo() {
-        let mock_commands = MockRepoCommands::new();
-        let cloner = RepoCloner::new(mock_commands);
-        cloner.run("https://github.com/author/project.git", "/base/path");
-
-        let cloned_repos = cloner.commands.cloned_repos.borrow();
-        assert_eq!(cloned_repos.len(), 1);
-        assert_eq!(cloned_repos[0].0, "https://github.com/author/project.git");
+    fn test_disable_fsmonitor_turns_off_both_monitors() {
+        let commands = system_commands(&parse(&["--disable-fsmonitor"]));
         assert_eq!(
-            cloned_repos[0].1,
-            PathBuf::from("/base/path/github.com/author/project")
+            commands.config(),
+            [
+                ("core.useBuiltinFSMonitor".to_string(), "false".to_string()),
+                ("core.fsmonitor".to_string(), "false".to_string()),
+            ]
         );
+    }
+
+    #[test]
+    fn test_dry_run_gets_the_same_git_settings() {
+        let args = parse(&[
+            "--dry-run",
+            "--disable-fsmonitor",
+            "--skip-hooks",
+            "--git-askpass-script",
+            "/bin/true",
+        ]);
+        let system = system_commands(&args);
+        let plan = dry_run_commands(&args).plan();
+
+        assert_eq!(plan.git_config, system.config());
+        assert_eq!(plan.git_config.len(), 3);
+        assert_eq!(plan.git_env, system.env());
+    }
 
-        let navigated_paths = cloner.commands.navigated_paths.borrow();
-        assert_eq!(navigated_paths.len(), 1);
+    #[test]
+    fn test_skip_hooks_sets_hooks_path() {
+        let commands = system_commands(&parse(&["--skip-hooks"]));
         assert_eq!(
-            navigated_paths[0],
-            PathBuf::from("/base/path/github.com/author/project")
+            commands.config(),
+            [("core.hooksPath".to_string(), "/dev/null".to_string())]
         );
-
-        let success = cloner.commands.success.take();
-        assert!(success);
+        assert!(system_commands(&parse(&[])).config().is_empty());
     }
 
+    #[cfg(feature = "aws")]
     #[test]
-    fn test_clone_libjpeg_turbo() {
-        let mock_commands = MockRepoCommands::new();
-        let cloner = RepoCloner::new(mock_commands);
-        cloner.run(
-            "https://github.com/libjpeg-turbo/libjpeg-turbo.git",
-            "/base/path",
-        );
+    fn test_aws_codecommit_credential_helper() {
+        let args = Args::try_parse_from([
+            "repo-cloner",
+            "https://git-codecommit.us-east-2.amazonaws.com/v1/repos/project",
+            "--aws-codecommit-credential-helper",
+        ])
+        .unwrap();
+
+        let commands = system_commands(&args);
 
-        let cloned_repos = cloner.commands.cloned_repos.borrow();
-        assert_eq!(cloned_repos.len(), 1);
         assert_eq!(
-            cloned_repos[0].0,
-            "https://github.com/libjpeg-turbo/libjpeg-turbo.git"
+            commands.config(),
+            [
+                (
+                    "credential.helper".to_string(),
+                    "!aws codecommit credential-helper $@".to_string()
+                ),
+                ("credential.UseHttpPath".to_string(), "true".to_string()),
+            ]
         );
         assert_eq!(
-            cloned_repos[0].1,
-            PathBuf::from("/base/path/github.com/libjpeg-turbo/libjpeg-turbo")
+            commands.env(),
+            [("AWS_DEFAULT_REGION".to_string(), "us-east-2".to_string())]
         );
+    }
 
-        let navigated_paths = cloner.commands.navigated_paths.borrow();
-        assert_eq!(navigated_paths.len(), 1);
+    #[test]
+    fn test_gcloud_credential_helper() {
+        let commands = system_commands(&parse(&["--gcloud-credential-helper"]));
         assert_eq!(
-            navigated_paths[0],
-            PathBuf::from("/base/path/github.com/libjpeg-turbo/libjpeg-turbo")
+            commands.config(),
+            [("credential.helper".to_string(), "gcloud.sh".to_string())]
         );
-
-        let success = cloner.commands.success.take();
-        assert!(success);
     }
 
     #[test]
-    fn test_clone_gitlab() {
-        let mock_commands = MockRepoCommands::new();
-        let cloner = RepoCloner::new(mock_commands);
-        cloner.run(
-            "https://gitlab.com/emeraldjayde/gitlab-vscode-extension.git",
-            "/base/path",
+    fn test_azure_credential_helper_only_for_azure_devops() {
+        let args = Args::try_parse_from([
+            "repo-cloner",
+            "https://org@dev.azure.com/org/project/_git/repo",
+            "--azure-credential-helper",
+        ])
+        .unwrap();
+        assert_eq!(
+            system_commands(&args).config(),
+            [("credential.helper".to_string(), "manager".to_string())]
         );
+        assert!(system_commands(&parse(&["--azure-credential-helper"]))
+            .config()
+            .is_empty());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_git_askpass_script_sets_git_askpass() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let script = dir.path().join("askpass.sh");
+        fs::write(&script, "#!/bin/sh\necho secret\n").unwrap();
+        fs::set_permissions(&script, fs::Permissions::from_mode(0o644)).unwrap();
+        let script_arg = script.to_str().unwrap();
+        assert!(Args::try_parse_from([
+            "repo-cloner",
+            "https://github.com/author/project.git",
+            "--git-askpass-script",
+            script_arg,
+        ])
+        .is_err());
+
+        fs::set_permissions(&script, fs::Permissions::from_mode(0o755)).unwrap();
+        let commands = system_commands(&parse(&["--git-askpass-script", script_arg]));
 
-        let cloned_repos = cloner.commands.cloned_repos.borrow();
-        assert_eq!(cloned_repos.len(), 1);
         assert_eq!(
-            cloned_repos[0].0,
-            "https://gitlab.com/emeraldjayde/gitlab-vscode-extension.git"
+            commands.env(),
+            [("GIT_ASKPASS".to_string(), script_arg.to_string())]
         );
+        assert!(askpass_script(dir.path().join("missing.sh").to_str().unwrap()).is_err());
+    }
+
+    #[test]
+    fn test_mirror_filter_takes_refspecs() {
+        let args = parse(&[
+            "--mirror-filter",
+            "+refs/heads/main:refs/heads/main",
+            "--mirror-filter",
+            "refs/heads/release/*:refs/remotes/origin/release/*",
+        ]);
         assert_eq!(
-            cloned_repos[0].1,
-            PathBuf::from("/base/path/gitlab.com/emeraldjayde/gitlab-vscode-extension")
+            cloner_options(&args).clone.mirror_filters,
+            vec![
+                "+refs/heads/main:refs/heads/main",
+                "refs/heads/release/*:refs/remotes/origin/release/*",
+            ]
         );
+        assert!(mirror_filter("refs/heads/main").is_err());
+        assert!(mirror_filter("+:refs/heads/main").is_err());
+    }
 
-        let navigated_paths = cloner.commands.navigated_paths.borrow();
-        assert_eq!(navigated_paths.len(), 1);
+    #[test]
+    fn test_bundle_uri_validation() {
         assert_eq!(
-            navigated_paths[0],
-            PathBuf::from("/base/path/gitlab.com/emeraldjayde/gitlab-vscode-extension")
+            parse(&["--bundle-uri", "https://cdn.example.com/project.bundle"]).bundle_uri,
+            Some("https://cdn.example.com/project.bundle".to_string())
         );
+        assert!(bundle_uri("file:///srv/bundles/project.bundle").is_ok());
+        assert!(bundle_uri("ssh://host/project.bundle").is_err());
+        assert!(bundle_uri("not a uri").is_err());
+    }
 
-        let success = cloner.commands.success.take();
-        assert!(success);
+    #[test]
+    fn test_sparse_patterns_from_file_and_command_line() {
+        let file = tempfile::NamedTempFile::new().unwrap();
+        fs::write(file.path(), "# docs\n/docs/\n\n*.md\n").unwrap();
+        let args = parse(&[
+            "--sparse",
+            "/src/",
+            "--sparse",
+            "*.md",
+            "--sparse-checkout-file",
+            file.path().to_str().unwrap(),
+        ]);
+
+        assert_eq!(sparse_patterns(&args), vec!["/src/", "*.md", "/docs/"]);
+    }
+
+    #[cfg(feature = "vault")]
+    #[test]
+    fn test_vault_credentials_are_injected_through_askpass() {
+        let credentials = Credentials {
+            username: "alice".to_string(),
+            password: "s3cret".to_string(),
+        };
+        let script = Path::new("/tmp/repo-cloner-askpass.sh");
+
+        let commands = with_credentials(SystemRepoCommands::new(), script, &credentials);
+
+        assert_eq!(
+            commands.env(),
+            [
+                ("GIT_ASKPASS".to_string(), script.display().to_string()),
+                (askpass::USERNAME_VAR.to_string(), "alice".to_string()),
+                (askpass::PASSWORD_VAR.to_string(), "s3cret".to_string()),
+            ]
+        );
     }
 }
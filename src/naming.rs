@@ -0,0 +1,228 @@
+use chrono::format::{Item, StrftimeItems};
+use chrono::{DateTime, Utc};
+use clap::ValueEnum;
+use std::borrow::Cow;
+
+/// Compact ISO 8601 UTC timestamp, e.g. `20240101T120000Z`.
+pub const DEFAULT_TIMESTAMP_FORMAT: &str = "%Y%m%dT%H%M%SZ";
+
+/// Name of the tag recording when a repository was cloned, e.g.
+/// `cloned-at-20240101T120000Z`.
+pub fn clone_date_tag(time: DateTime<Utc>) -> String {
+    format!("cloned-at-{}", time.format(DEFAULT_TIMESTAMP_FORMAT))
+}
+
+/// Appends `time`, rendered with the strftime `format`, to a project name,
+/// e.g. `project-20240101T120000Z`.
+pub fn append_timestamp(name: &str, time: DateTime<Utc>, format: &str) -> String {
+    format!("{}-{}", name, time.format(format))
+}
+
+/// Checks that `format` is a strftime format usable in a directory name.
+pub fn check_timestamp_format(format: &str) -> Result<(), String> {
+    if format.is_empty() || format.contains('/') {
+        return Err(format!(
+            "invalid timestamp format '{}', expected a non-empty format without '/'",
+            format
+        ));
+    }
+    if StrftimeItems::new(format).any(|item| item == Item::Error) {
+        return Err(format!("invalid strftime format '{}'", format));
+    }
+    Ok(())
+}
+
+/// Removes a trailing `.git` from a project name, leaving `.git` elsewhere in
+/// the name (`my.git.project`, `project.gitconfig`) untouched.
+pub fn strip_git_suffix(name: &str) -> Cow<'_, str> {
+    match name.strip_suffix(".git") {
+        Some(stripped) if !stripped.is_empty() => Cow::Owned(stripped.to_string()),
+        _ => Cow::Borrowed(name),
+    }
+}
+
+/// Casing applied to the project directory name.
+#[derive(Clone, Copy, Debug, PartialEq, ValueEnum)]
+pub enum CaseConverter {
+    /// `MyProject` -> `myproject`
+    Lower,
+    /// `MyProject` -> `MYPROJECT`
+    Upper,
+    /// `my-project` -> `My-Project`, `myProject` -> `MyProject`
+    Title,
+    /// `MyProject` -> `my-project`
+    Kebab,
+    /// `MyProject` -> `my_project`
+    Snake,
+}
+
+/// Converts `name` to the given case.
+pub fn apply(name: &str, case: CaseConverter) -> String {
+    match case {
+        CaseConverter::Lower => name.to_lowercase(),
+        CaseConverter::Upper => name.to_uppercase(),
+        CaseConverter::Title => title_case(name),
+        CaseConverter::Kebab => join_words(name, "-"),
+        CaseConverter::Snake => join_words(name, "_"),
+    }
+}
+
+/// Splits an alphanumeric run into words at camelCase and acronym boundaries,
+/// e.g. `HTTPServerV2` -> `HTTP`, `Server`, `V2`.
+fn camel_words(part: &str) -> Vec<&str> {
+    let chars: Vec<(usize, char)> = part.char_indices().collect();
+    let mut words = Vec::new();
+    let mut start = 0;
+    for i in 1..chars.len() {
+        let (index, current) = chars[i];
+        let previous = chars[i - 1].1;
+        let next_is_lower = chars.get(i + 1).is_some_and(|(_, c)| c.is_lowercase());
+        let boundary = current.is_uppercase()
+            && (previous.is_lowercase()
+                || previous.is_ascii_digit()
+                || (previous.is_uppercase() && next_is_lower));
+        if boundary {
+            words.push(&part[start..index]);
+            start = index;
+        }
+    }
+    if start < part.len() {
+        words.push(&part[start..]);
+    }
+    words
+}
+
+fn words(name: &str) -> Vec<&str> {
+    name.split(|c: char| !c.is_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .flat_map(camel_words)
+        .collect()
+}
+
+fn join_words(name: &str, separator: &str) -> String {
+    words(name)
+        .iter()
+        .map(|word| word.to_lowercase())
+        .collect::<Vec<_>>()
+        .join(separator)
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first
+            .to_uppercase()
+            .chain(chars.flat_map(char::to_lowercase))
+            .collect(),
+        None => String::new(),
+    }
+}
+
+/// Capitalizes each word while keeping the original separators.
+fn title_case(name: &str) -> String {
+    let mut result = String::with_capacity(name.len());
+    let mut part_start = None;
+    for (index, c) in name.char_indices() {
+        match (c.is_alphanumeric(), part_start) {
+            (true, None) => part_start = Some(index),
+            (false, Some(start)) => {
+                result.extend(camel_words(&name[start..index]).into_iter().map(capitalize));
+                result.push(c);
+                part_start = None;
+            }
+            (false, None) => result.push(c),
+            (true, Some(_)) => {}
+        }
+    }
+    if let Some(start) = part_start {
+        result.extend(camel_words(&name[start..]).into_iter().map(capitalize));
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_clone_date_tag() {
+        let time = Utc.with_ymd_and_hms(2024, 1, 1, 12, 0, 0).unwrap();
+        assert_eq!(clone_date_tag(time), "cloned-at-20240101T120000Z");
+    }
+
+    #[test]
+    fn test_append_timestamp() {
+        let time = Utc.with_ymd_and_hms(2024, 1, 1, 12, 0, 0).unwrap();
+        assert_eq!(
+            append_timestamp("project", time, DEFAULT_TIMESTAMP_FORMAT),
+            "project-20240101T120000Z"
+        );
+        assert_eq!(
+            append_timestamp("project", time, "%Y-%m-%d"),
+            "project-2024-01-01"
+        );
+    }
+
+    #[test]
+    fn test_check_timestamp_format() {
+        assert!(check_timestamp_format(DEFAULT_TIMESTAMP_FORMAT).is_ok());
+        assert!(check_timestamp_format("%Y-%m-%d_%H%M").is_ok());
+        assert!(check_timestamp_format("%Y/%m").is_err());
+        assert!(check_timestamp_format("%Q").is_err());
+        assert!(check_timestamp_format("").is_err());
+    }
+
+    #[test]
+    fn test_strip_git_suffix() {
+        assert_eq!(strip_git_suffix("project.git"), "project");
+        assert_eq!(strip_git_suffix("my.git.project"), "my.git.project");
+        assert_eq!(strip_git_suffix("project.gitconfig"), "project.gitconfig");
+        assert_eq!(strip_git_suffix("project"), "project");
+        assert_eq!(strip_git_suffix(".git"), ".git");
+    }
+
+    #[test]
+    fn test_lower_and_upper() {
+        assert_eq!(
+            apply("MyProject-Core", CaseConverter::Lower),
+            "myproject-core"
+        );
+        assert_eq!(
+            apply("MyProject-Core", CaseConverter::Upper),
+            "MYPROJECT-CORE"
+        );
+    }
+
+    #[test]
+    fn test_title() {
+        assert_eq!(apply("my-project", CaseConverter::Title), "My-Project");
+        assert_eq!(apply("myProject", CaseConverter::Title), "MyProject");
+        assert_eq!(
+            apply("libjpeg_TURBO", CaseConverter::Title),
+            "Libjpeg_Turbo"
+        );
+    }
+
+    #[test]
+    fn test_kebab() {
+        assert_eq!(apply("MyProject", CaseConverter::Kebab), "my-project");
+        assert_eq!(
+            apply("HTTPServer_v2", CaseConverter::Kebab),
+            "http-server-v2"
+        );
+        assert_eq!(
+            apply("gitlab-vscode-extension", CaseConverter::Kebab),
+            "gitlab-vscode-extension"
+        );
+    }
+
+    #[test]
+    fn test_snake() {
+        assert_eq!(apply("MyProject", CaseConverter::Snake), "my_project");
+        assert_eq!(
+            apply("my-Cool.Project", CaseConverter::Snake),
+            "my_cool_project"
+        );
+    }
+}
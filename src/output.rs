@@ -0,0 +1,182 @@
+use serde::Serialize;
+use std::io::{self, Write};
+use std::path::PathBuf;
+
+/// How clone results are reported.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum OutputFormat {
+    /// Human-readable messages only (`cd <path>`, success message).
+    #[default]
+    Text,
+    /// One JSON `CloneResult` object per line, written as each clone finishes.
+    JsonLines,
+    /// A header row followed by one CSV row per clone.
+    Csv,
+    /// Like `Csv`, but TAB-separated and never quoted, for `awk` and `cut`.
+    Tsv,
+}
+
+/// Column names for the CSV output, in `CloneResult` field order.
+const CSV_HEADER: [&str; 5] = ["url", "path", "success", "duration_ms", "error"];
+
+/// The outcome of a single `git clone`.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct CloneResult {
+    pub url: String,
+    pub path: PathBuf,
+    pub success: bool,
+    pub duration_ms: u128,
+    pub error: Option<String>,
+}
+
+/// Writes `CloneResult`s to `out` in a machine-readable format.
+pub struct ResultWriter<W: Write> {
+    format: OutputFormat,
+    out: W,
+}
+
+impl<W: Write> ResultWriter<W> {
+    pub fn new(format: OutputFormat, out: W) -> Self {
+        ResultWriter { format, out }
+    }
+
+    pub fn into_inner(self) -> W {
+        self.out
+    }
+
+    /// Writes anything that must precede the first result, such as a CSV header.
+    pub fn write_header(&mut self) -> io::Result<()> {
+        match self.format {
+            OutputFormat::Text | OutputFormat::JsonLines => Ok(()),
+            OutputFormat::Csv | OutputFormat::Tsv => {
+                let mut writer = self.csv_writer();
+                writer.write_record(CSV_HEADER)?;
+                writer.flush()
+            }
+        }
+    }
+
+    fn csv_writer(&mut self) -> csv::Writer<&mut W> {
+        let mut builder = csv::WriterBuilder::new();
+        builder.has_headers(false);
+        if self.format == OutputFormat::Tsv {
+            builder.delimiter(b'\t').quote_style(csv::QuoteStyle::Never);
+        }
+        builder.from_writer(&mut self.out)
+    }
+
+    pub fn write(&mut self, result: &CloneResult) -> io::Result<()> {
+        match self.format {
+            OutputFormat::Text => Ok(()),
+            OutputFormat::JsonLines => {
+                serde_json::to_writer(&mut self.out, result)?;
+                writeln!(self.out)?;
+                self.out.flush()
+            }
+            OutputFormat::Csv | OutputFormat::Tsv => {
+                let mut writer = self.csv_writer();
+                writer.serialize(result)?;
+                writer.flush()
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_results() -> Vec<CloneResult> {
+        vec![
+            CloneResult {
+                url: "https://github.com/author/project.git".to_string(),
+                path: PathBuf::from("/base/path/github.com/author/project"),
+                success: true,
+                duration_ms: 1200,
+                error: None,
+            },
+            CloneResult {
+                url: "https://gitlab.com/other/lib.git".to_string(),
+                path: PathBuf::from("/base/path/gitlab.com/other/lib"),
+                success: false,
+                duration_ms: 40,
+                error: Some("git clone exited with \"128\"".to_string()),
+            },
+        ]
+    }
+
+    #[test]
+    fn test_json_lines_are_independent_json_objects() {
+        let mut writer = ResultWriter::new(OutputFormat::JsonLines, Vec::new());
+        for result in sample_results() {
+            writer.write(&result).unwrap();
+        }
+
+        let contents = String::from_utf8(writer.into_inner()).unwrap();
+        let lines: Vec<_> = contents.lines().collect();
+        assert_eq!(lines.len(), 2);
+        for (line, expected) in lines.iter().zip(sample_results()) {
+            let value: serde_json::Value = serde_json::from_str(line).unwrap();
+            assert_eq!(value["url"], expected.url);
+            assert_eq!(value["success"], expected.success);
+            assert_eq!(value["duration_ms"], expected.duration_ms as u64);
+        }
+        let failed: serde_json::Value = serde_json::from_str(lines[1]).unwrap();
+        assert_eq!(failed["error"], "git clone exited with \"128\"");
+    }
+
+    #[test]
+    fn test_csv_rows_parse_with_expected_fields() {
+        let mut writer = ResultWriter::new(OutputFormat::Csv, Vec::new());
+        writer.write_header().unwrap();
+        for result in sample_results() {
+            writer.write(&result).unwrap();
+        }
+
+        let output = writer.into_inner();
+        let mut reader = csv::Reader::from_reader(output.as_slice());
+        assert_eq!(reader.headers().unwrap(), CSV_HEADER.as_slice());
+        let records: Vec<csv::StringRecord> = reader.records().map(Result::unwrap).collect();
+        assert_eq!(records.len(), 2);
+        for (record, expected) in records.iter().zip(sample_results()) {
+            assert_eq!(record.len(), 5);
+            assert_eq!(&record[0], expected.url);
+            assert_eq!(record[1], *expected.path.to_string_lossy());
+            assert_eq!(record[2].parse::<bool>().unwrap(), expected.success);
+            assert_eq!(record[3].parse::<u128>().unwrap(), expected.duration_ms);
+            assert_eq!(&record[4], expected.error.as_deref().unwrap_or(""));
+        }
+    }
+
+    #[test]
+    fn test_tsv_uses_tabs_without_quotes() {
+        let mut writer = ResultWriter::new(OutputFormat::Tsv, Vec::new());
+        writer.write_header().unwrap();
+        for result in sample_results() {
+            writer.write(&result).unwrap();
+        }
+
+        let contents = String::from_utf8(writer.into_inner()).unwrap();
+        let lines: Vec<_> = contents.lines().collect();
+        assert_eq!(lines[0], "url\tpath\tsuccess\tduration_ms\terror");
+        assert_eq!(
+            lines[1],
+            "https://github.com/author/project.git\t/base/path/github.com/author/project\ttrue\t1200\t"
+        );
+        assert_eq!(
+            lines[2],
+            "https://gitlab.com/other/lib.git\t/base/path/gitlab.com/other/lib\tfalse\t40\tgit clone exited with \"128\""
+        );
+        for line in lines {
+            assert_eq!(line.split('\t').count(), 5);
+            assert!(!line.contains(','));
+        }
+    }
+
+    #[test]
+    fn test_text_format_writes_nothing() {
+        let mut writer = ResultWriter::new(OutputFormat::Text, Vec::new());
+        writer.write(&sample_results()[0]).unwrap();
+        assert!(writer.into_inner().is_empty());
+    }
+}
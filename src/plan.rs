@@ -0,0 +1,319 @@
+use crate::github::{self, CommitStatus};
+use crate::shell;
+use std::fmt;
+use std::path::PathBuf;
+
+/// A single step a run would perform.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Operation {
+    CreateDirAll(PathBuf),
+    GitClone {
+        url: String,
+        path: PathBuf,
+        args: Vec<String>,
+    },
+    Cd(PathBuf),
+    DisplaySuccess,
+    GitLsRemote(String),
+    GitConfig {
+        repo: PathBuf,
+        key: String,
+        value: String,
+    },
+    GitSubmoduleUpdate(PathBuf),
+    GitRemoteAdd {
+        repo: PathBuf,
+        name: String,
+        url: String,
+    },
+    GitRemoteSetUrl {
+        repo: PathBuf,
+        name: String,
+        url: String,
+    },
+    GitBundleCreate {
+        repo: PathBuf,
+        output: PathBuf,
+    },
+    GitTag {
+        repo: PathBuf,
+        name: String,
+    },
+    PreCommitInstall(PathBuf),
+    InstallHooks {
+        hooks_src: PathBuf,
+        git_dir: PathBuf,
+    },
+    GitLocalBranches(PathBuf),
+    GitAddSafeDirectory(PathBuf),
+    RemoveDirAll(PathBuf),
+    GitVerifyPack(PathBuf),
+    GitGcAggressive(PathBuf),
+    GitRepack {
+        repo: PathBuf,
+        depth: u32,
+    },
+    AddAlternate {
+        git_dir: PathBuf,
+        alternate: PathBuf,
+    },
+    InjectGitattributes {
+        src: PathBuf,
+        repo: PathBuf,
+    },
+    GitCheckout(PathBuf),
+    WriteFile {
+        path: PathBuf,
+        contents: String,
+    },
+    GitSparseCheckoutSet {
+        repo: PathBuf,
+        patterns: Vec<String>,
+    },
+    GitRevParseHead(PathBuf),
+    PostCommitStatus(CommitStatus),
+    GitConfigAdd {
+        repo: PathBuf,
+        key: String,
+        value: String,
+    },
+}
+
+impl Operation {
+    /// Whether running this operation spawns a process, as opposed to
+    /// touching the filesystem or printing.
+    pub fn spawns_process(&self) -> bool {
+        !matches!(
+            self,
+            Operation::CreateDirAll(_)
+                | Operation::Cd(_)
+                | Operation::DisplaySuccess
+                | Operation::InstallHooks { .. }
+                | Operation::RemoveDirAll(_)
+                | Operation::AddAlternate { .. }
+                | Operation::InjectGitattributes { .. }
+                | Operation::WriteFile { .. }
+                | Operation::PostCommitStatus(_)
+        )
+    }
+
+    /// The program and arguments `SystemRepoCommands` spawns for this
+    /// operation, or `None` when its display is already a shell command
+    /// line (or nothing is spawned).
+    pub fn argv(&self) -> Option<Vec<String>> {
+        let git_in = |repo: &PathBuf, args: &[&str]| {
+            let mut argv = vec![
+                "git".to_string(),
+                "-C".to_string(),
+                repo.display().to_string(),
+            ];
+            argv.extend(args.iter().map(|arg| arg.to_string()));
+            argv
+        };
+        let argv = match self {
+            Operation::GitClone { url, path, args } => {
+                let mut argv = vec!["git".to_string(), "clone".to_string()];
+                argv.extend(args.iter().cloned());
+                argv.push(url.clone());
+                argv.push(path.display().to_string());
+                argv
+            }
+            Operation::GitLsRemote(url) => {
+                vec!["git".to_string(), "ls-remote".to_string(), url.clone()]
+            }
+            Operation::GitConfig { repo, key, value } => git_in(repo, &["config", key, value]),
+            Operation::GitSubmoduleUpdate(repo) => git_in(
+                repo,
+                &["-c", "protocol.file.allow=always", "submodule", "update"],
+            ),
+            Operation::GitRemoteAdd { repo, name, url } => {
+                git_in(repo, &["remote", "add", name, url])
+            }
+            Operation::GitRemoteSetUrl { repo, name, url } => {
+                git_in(repo, &["remote", "set-url", name, url])
+            }
+            Operation::GitBundleCreate { repo, output } => git_in(
+                repo,
+                &["bundle", "create", &output.display().to_string(), "--all"],
+            ),
+            Operation::GitTag { repo, name } => git_in(repo, &["tag", name]),
+            Operation::GitLocalBranches(repo) => git_in(
+                repo,
+                &["for-each-ref", "--format=%(refname:short)", "refs/heads"],
+            ),
+            Operation::GitAddSafeDirectory(path) => vec![
+                "git".to_string(),
+                "config".to_string(),
+                "--global".to_string(),
+                "--add".to_string(),
+                "safe.directory".to_string(),
+                path.display().to_string(),
+            ],
+            Operation::GitGcAggressive(repo) => git_in(repo, &["gc", "--aggressive"]),
+            Operation::GitRepack { repo, depth } => {
+                git_in(repo, &["repack", "-a", "-d", &format!("--depth={}", depth)])
+            }
+            Operation::GitCheckout(repo) => git_in(repo, &["checkout"]),
+            Operation::GitSparseCheckoutSet { repo, patterns } => {
+                let mut argv = git_in(repo, &["sparse-checkout", "set", "--no-cone"]);
+                argv.extend(patterns.iter().cloned());
+                argv
+            }
+            Operation::GitRevParseHead(repo) => git_in(repo, &["rev-parse", "HEAD"]),
+            Operation::GitConfigAdd { repo, key, value } => {
+                git_in(repo, &["config", "--add", key, value])
+            }
+            _ => return None,
+        };
+        Some(argv)
+    }
+}
+
+impl fmt::Display for Operation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Operation::CreateDirAll(path) => write!(f, "mkdir -p {}", path.display()),
+            Operation::GitClone { url, path, args } => {
+                write!(f, "git clone ")?;
+                for arg in args {
+                    write!(f, "{} ", arg)?;
+                }
+                write!(f, "{} {}", url, path.display())
+            }
+            Operation::Cd(path) => write!(f, "cd {}", path.display()),
+            Operation::DisplaySuccess => write!(f, "Repository cloned successfully."),
+            Operation::GitLsRemote(url) => write!(f, "git ls-remote {}", url),
+            Operation::GitConfig { repo, key, value } => {
+                write!(f, "git -C {} config {} {}", repo.display(), key, value)
+            }
+            Operation::GitSubmoduleUpdate(repo) => {
+                write!(
+                    f,
+                    "git -C {} -c protocol.file.allow=always submodule update",
+                    repo.display()
+                )
+            }
+            Operation::GitRemoteAdd { repo, name, url } => {
+                write!(f, "git -C {} remote add {} {}", repo.display(), name, url)
+            }
+            Operation::GitRemoteSetUrl { repo, name, url } => {
+                write!(
+                    f,
+                    "git -C {} remote set-url {} {}",
+                    repo.display(),
+                    name,
+                    url
+                )
+            }
+            Operation::GitBundleCreate { repo, output } => write!(
+                f,
+                "git -C {} bundle create {} --all",
+                repo.display(),
+                output.display()
+            ),
+            Operation::GitTag { repo, name } => {
+                write!(f, "git -C {} tag {}", repo.display(), name)
+            }
+            Operation::PreCommitInstall(repo) => {
+                write!(f, "cd {} && pre-commit install", repo.display())
+            }
+            Operation::InstallHooks { hooks_src, git_dir } => write!(
+                f,
+                "cp {}/* {}/hooks/",
+                hooks_src.display(),
+                git_dir.display()
+            ),
+            Operation::GitLocalBranches(repo) => write!(
+                f,
+                "git -C {} for-each-ref --format=%(refname:short) refs/heads",
+                repo.display()
+            ),
+            Operation::GitAddSafeDirectory(path) => write!(
+                f,
+                "git config --global --add safe.directory {}",
+                path.display()
+            ),
+            Operation::RemoveDirAll(path) => write!(f, "rm -rf {}", path.display()),
+            Operation::GitVerifyPack(repo) => write!(
+                f,
+                "git verify-pack -v {}/.git/objects/pack/*.idx",
+                repo.display()
+            ),
+            Operation::GitGcAggressive(repo) => {
+                write!(f, "git -C {} gc --aggressive", repo.display())
+            }
+            Operation::GitRepack { repo, depth } => write!(
+                f,
+                "git -C {} repack -a -d --depth={}",
+                repo.display(),
+                depth
+            ),
+            Operation::AddAlternate { git_dir, alternate } => write!(
+                f,
+                "echo {}/.git/objects >> {}/objects/info/alternates",
+                alternate.display(),
+                git_dir.display()
+            ),
+            Operation::InjectGitattributes { src, repo } => {
+                write!(
+                    f,
+                    "cp {} \"$(git -C {} rev-parse --git-path info/attributes)\"",
+                    src.display(),
+                    repo.display()
+                )
+            }
+            Operation::GitCheckout(repo) => write!(f, "git -C {} checkout", repo.display()),
+            Operation::WriteFile { path, contents } => write!(
+                f,
+                "printf %s {} > {}",
+                shell::quote(contents),
+                path.display()
+            ),
+            Operation::GitSparseCheckoutSet { repo, patterns } => {
+                write!(f, "git -C {} sparse-checkout set --no-cone", repo.display())?;
+                for pattern in patterns {
+                    write!(f, " {}", pattern)?;
+                }
+                Ok(())
+            }
+            Operation::GitRevParseHead(repo) => {
+                write!(f, "git -C {} rev-parse HEAD", repo.display())
+            }
+            Operation::PostCommitStatus(status) => write!(
+                f,
+                "POST {}/repos/{}/statuses/{} state={} context={}",
+                github::DEFAULT_API_BASE,
+                status.repo,
+                status.sha,
+                status.state,
+                status.context
+            ),
+            Operation::GitConfigAdd { repo, key, value } => {
+                write!(
+                    f,
+                    "git -C {} config --add {} {}",
+                    repo.display(),
+                    key,
+                    value
+                )
+            }
+        }
+    }
+}
+
+/// The ordered list of operations recorded by a dry run, so callers can
+/// inspect what a run would do instead of parsing printed output.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Plan {
+    pub operations: Vec<Operation>,
+    /// `-c key=value` overrides every git command in the plan runs with.
+    pub git_config: Vec<(String, String)>,
+    /// Environment variables every git command in the plan runs with.
+    pub git_env: Vec<(String, String)>,
+}
+
+impl Plan {
+    pub fn push(&mut self, operation: Operation) {
+        self.operations.push(operation);
+    }
+}
@@ -0,0 +1,85 @@
+/// A named git remote to configure after cloning.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Remote {
+    pub name: String,
+    pub url: String,
+}
+
+/// Parses a `--multi-origin` value: either a bare URL, added as the `ssh`
+/// remote, or comma-separated `name=url` pairs.
+pub fn parse_remotes(value: &str) -> Result<Vec<Remote>, String> {
+    let value = value.trim();
+    if value.is_empty() {
+        return Err("expected a URL or name=url pairs".to_string());
+    }
+
+    let pairs: Option<Vec<Remote>> = value
+        .split(',')
+        .map(|pair| {
+            let (name, url) = pair.split_once('=')?;
+            let (name, url) = (name.trim(), url.trim());
+            let valid_name = !name.is_empty()
+                && name
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+            (valid_name && !url.is_empty()).then(|| Remote {
+                name: name.to_string(),
+                url: url.to_string(),
+            })
+        })
+        .collect();
+
+    match pairs {
+        Some(remotes) => Ok(remotes),
+        None if value.contains(',') => Err(format!(
+            "invalid remote list '{}', expected name=url pairs separated by commas",
+            value
+        )),
+        None => Ok(vec![Remote {
+            name: "ssh".to_string(),
+            url: value.to_string(),
+        }]),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_bare_url_as_ssh_remote() {
+        assert_eq!(
+            parse_remotes("git@github.com:author/project.git").unwrap(),
+            vec![Remote {
+                name: "ssh".to_string(),
+                url: "git@github.com:author/project.git".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_parse_named_remotes() {
+        assert_eq!(
+            parse_remotes(
+                "origin=https://github.com/author/project.git,ssh=git@github.com:author/project.git"
+            )
+            .unwrap(),
+            vec![
+                Remote {
+                    name: "origin".to_string(),
+                    url: "https://github.com/author/project.git".to_string(),
+                },
+                Remote {
+                    name: "ssh".to_string(),
+                    url: "git@github.com:author/project.git".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_invalid_remotes() {
+        assert!(parse_remotes("").is_err());
+        assert!(parse_remotes("ssh=git@host:a/b.git,oops").is_err());
+    }
+}
@@ -0,0 +1,115 @@
+use std::borrow::Cow;
+use std::ffi::OsStr;
+use std::process::Command;
+
+/// Quotes `word` for a POSIX shell, leaving it untouched when no quoting is needed.
+pub fn quote(word: &str) -> Cow<'_, str> {
+    let safe = !word.is_empty()
+        && word
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "_-./:=@%+,".contains(c));
+    if safe {
+        Cow::Borrowed(word)
+    } else {
+        Cow::Owned(format!("'{}'", word.replace('\'', r"'\''")))
+    }
+}
+
+/// Renders a program and its arguments as a single shell command line.
+pub fn join<'a>(parts: impl IntoIterator<Item = &'a OsStr>) -> String {
+    parts
+        .into_iter()
+        .map(|part| quote(&part.to_string_lossy()).into_owned())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Renders `vars` as `export NAME=value` lines that `sh` can source.
+pub fn exports(vars: &[(&str, &str)]) -> String {
+    vars.iter()
+        .map(|(name, value)| format!("export {}={}\n", name, quote(value)))
+        .collect()
+}
+
+/// Rewrites `command` as `sh -c "<command line>"`, keeping its environment
+/// and working directory.
+pub fn wrap(command: &Command) -> Command {
+    let mut shell = Command::new("sh");
+    shell.arg("-c").arg(join(
+        std::iter::once(command.get_program()).chain(command.get_args()),
+    ));
+    if let Some(dir) = command.get_current_dir() {
+        shell.current_dir(dir);
+    }
+    for (key, value) in command.get_envs() {
+        match value {
+            Some(value) => shell.env(key, value),
+            None => shell.env_remove(key),
+        };
+    }
+    shell
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quote() {
+        assert_eq!(
+            quote("https://github.com/a/b.git"),
+            "https://github.com/a/b.git"
+        );
+        assert_eq!(quote("my repo"), "'my repo'");
+        assert_eq!(quote("it's"), r"'it'\''s'");
+        assert_eq!(quote(""), "''");
+    }
+
+    #[test]
+    fn test_exports_are_sourced_by_sh() {
+        let script = exports(&[("REPO_URL", "https://x/a b.git"), ("REPO_AUTHOR", "o'neil")]);
+        assert_eq!(
+            script,
+            "export REPO_URL='https://x/a b.git'\nexport REPO_AUTHOR='o'\\''neil'\n"
+        );
+
+        let output = Command::new("sh")
+            .arg("-c")
+            .arg(format!(
+                "{}printf '%s|%s' \"$REPO_URL\" \"$REPO_AUTHOR\"",
+                script
+            ))
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+        assert_eq!(
+            String::from_utf8_lossy(&output.stdout),
+            "https://x/a b.git|o'neil"
+        );
+    }
+
+    #[test]
+    fn test_wrap_keeps_environment_and_directory() {
+        let mut command = Command::new("git");
+        command
+            .args(["clone", "https://github.com/a/b.git", "/tmp/my repo"])
+            .current_dir("/tmp")
+            .env("GIT_TERMINAL_PROMPT", "0");
+
+        let wrapped = wrap(&command);
+        assert_eq!(wrapped.get_program(), "sh");
+        let args: Vec<_> = wrapped.get_args().collect();
+        assert_eq!(
+            args,
+            ["-c", "git clone https://github.com/a/b.git '/tmp/my repo'"]
+        );
+        assert_eq!(
+            wrapped.get_current_dir(),
+            Some(std::path::Path::new("/tmp"))
+        );
+        assert_eq!(
+            wrapped.get_envs().collect::<Vec<_>>(),
+            [(OsStr::new("GIT_TERMINAL_PROMPT"), Some(OsStr::new("0")))]
+        );
+    }
+}
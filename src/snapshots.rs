@@ -0,0 +1,114 @@
+use crate::RepoCommands;
+use chrono::{NaiveDate, NaiveDateTime};
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Parses the timestamp `append_timestamp` added to `name`, if `name` is a
+/// timestamped copy of `project`. Formats without a time of day are read as
+/// midnight.
+fn copy_timestamp(name: &str, project: &str, format: &str) -> Option<NaiveDateTime> {
+    let suffix = name.strip_prefix(project)?.strip_prefix('-')?;
+    NaiveDateTime::parse_from_str(suffix, format)
+        .ok()
+        .or_else(|| {
+            NaiveDate::parse_from_str(suffix, format)
+                .ok()?
+                .and_hms_opt(0, 0, 0)
+        })
+}
+
+/// Removes all but the `keep` most recent timestamped copies of `project`
+/// in `dir`, returning the removed paths oldest first. Entries whose suffix
+/// doesn't parse with `format` aren't copies and are left alone.
+pub fn prune_copies<C: RepoCommands>(
+    commands: &C,
+    dir: &Path,
+    project: &str,
+    format: &str,
+    keep: usize,
+) -> io::Result<Vec<PathBuf>> {
+    let mut copies: Vec<(NaiveDateTime, PathBuf)> = commands
+        .read_dir(dir)?
+        .into_iter()
+        .filter_map(|path| {
+            let name = path.file_name()?.to_str()?;
+            Some((copy_timestamp(name, project, format)?, path))
+        })
+        .collect();
+    copies.sort();
+
+    let excess = copies.len().saturating_sub(keep);
+    let removed: Vec<PathBuf> = copies
+        .into_iter()
+        .take(excess)
+        .map(|(_, path)| path)
+        .collect();
+    for path in &removed {
+        commands.remove_dir_all(path)?;
+    }
+    Ok(removed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::naming::DEFAULT_TIMESTAMP_FORMAT;
+    use crate::SystemRepoCommands;
+    use std::fs;
+
+    #[test]
+    fn test_copy_timestamp() {
+        let format = DEFAULT_TIMESTAMP_FORMAT;
+        assert!(copy_timestamp("project-20240101T120000Z", "project", format).is_some());
+        assert!(copy_timestamp("project-2024-01-01", "project", "%Y-%m-%d").is_some());
+        assert!(copy_timestamp("project", "project", format).is_none());
+        assert!(copy_timestamp("project-core-20240101T120000Z", "project", format).is_none());
+        assert!(copy_timestamp("other-20240101T120000Z", "project", format).is_none());
+    }
+
+    #[test]
+    fn test_prune_copies_keeps_most_recent() {
+        let dir = tempfile::tempdir().unwrap();
+        for name in [
+            "project-20240301T000000Z",
+            "project-20231231T235959Z",
+            "project-20240101T120000Z",
+            "project-20240215T080000Z",
+            "project-core-20230101T000000Z",
+            "project",
+        ] {
+            fs::create_dir(dir.path().join(name)).unwrap();
+        }
+
+        let removed = prune_copies(
+            &SystemRepoCommands::default(),
+            dir.path(),
+            "project",
+            DEFAULT_TIMESTAMP_FORMAT,
+            2,
+        )
+        .unwrap();
+
+        assert_eq!(
+            removed,
+            [
+                dir.path().join("project-20231231T235959Z"),
+                dir.path().join("project-20240101T120000Z"),
+            ]
+        );
+        let mut remaining: Vec<_> = fs::read_dir(dir.path())
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+            .collect();
+        remaining.sort();
+        assert_eq!(
+            remaining,
+            [
+                "project",
+                "project-20240215T080000Z",
+                "project-20240301T000000Z",
+                "project-core-20230101T000000Z",
+            ]
+        );
+    }
+}
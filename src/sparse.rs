@@ -0,0 +1,50 @@
+/// Parses a sparse checkout pattern file: one gitignore-style pattern per
+/// line, skipping blank lines and `#` comments.
+pub fn parse_sparse_patterns(contents: &str) -> Vec<String> {
+    contents
+        .lines()
+        .map(str::trim_end)
+        .filter(|line| !line.trim_start().is_empty() && !line.trim_start().starts_with('#'))
+        .map(str::to_string)
+        .collect()
+}
+
+/// Combines patterns from the command line and from a pattern file, keeping
+/// the first occurrence of each.
+pub fn merge_patterns(
+    command_line: impl IntoIterator<Item = String>,
+    file: impl IntoIterator<Item = String>,
+) -> Vec<String> {
+    let mut merged: Vec<String> = Vec::new();
+    for pattern in command_line.into_iter().chain(file) {
+        if !merged.contains(&pattern) {
+            merged.push(pattern);
+        }
+    }
+    merged
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_skips_blank_lines_and_comments() {
+        let contents = "# docs only\n/docs/\n\n   \n  # indented comment\n*.md  \n!/docs/drafts/\n";
+        assert_eq!(
+            parse_sparse_patterns(contents),
+            vec!["/docs/", "*.md", "!/docs/drafts/"]
+        );
+    }
+
+    #[test]
+    fn test_merge_is_a_union() {
+        assert_eq!(
+            merge_patterns(
+                vec!["/src/".to_string(), "*.md".to_string()],
+                vec!["*.md".to_string(), "/docs/".to_string()]
+            ),
+            vec!["/src/", "*.md", "/docs/"]
+        );
+    }
+}
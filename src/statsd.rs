@@ -0,0 +1,83 @@
+use crate::output::CloneResult;
+use cadence::prelude::*;
+use cadence::{MetricError, StatsdClient, UdpMetricSink};
+use std::io;
+use std::net::{ToSocketAddrs, UdpSocket};
+
+/// Prefix of every metric name.
+const PREFIX: &str = "repo_cloner";
+
+/// Sends per-clone metrics to a StatsD server: a `clone.duration_ms`
+/// timing and a `clone.success` or `clone.failure` count, all prefixed
+/// with `repo_cloner.`.
+pub struct StatsdReporter {
+    client: StatsdClient,
+}
+
+impl StatsdReporter {
+    /// Reports to the StatsD server at `addr` (`host:port`) over UDP.
+    pub fn new(addr: impl ToSocketAddrs) -> io::Result<Self> {
+        let socket = UdpSocket::bind("0.0.0.0:0")?;
+        let sink = UdpMetricSink::from(addr, socket).map_err(metric_error)?;
+        Ok(StatsdReporter {
+            client: StatsdClient::from_sink(PREFIX, sink),
+        })
+    }
+
+    pub fn record_clone(&self, result: &CloneResult) -> io::Result<()> {
+        let duration_ms = u64::try_from(result.duration_ms).unwrap_or(u64::MAX);
+        self.client
+            .time("clone.duration_ms", duration_ms)
+            .map_err(metric_error)?;
+        let outcome = if result.success {
+            "clone.success"
+        } else {
+            "clone.failure"
+        };
+        self.client.incr(outcome).map_err(metric_error)?;
+        Ok(())
+    }
+}
+
+fn metric_error(err: MetricError) -> io::Error {
+    io::Error::other(format!("failed to send StatsD metrics: {}", err))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+    use std::time::Duration;
+
+    fn receive(listener: &UdpSocket) -> String {
+        let mut datagram = [0; 512];
+        let len = listener.recv(&mut datagram).unwrap();
+        String::from_utf8(datagram[..len].to_vec()).unwrap()
+    }
+
+    #[test]
+    fn test_record_clone_sends_timing_and_outcome() {
+        let listener = UdpSocket::bind("127.0.0.1:0").unwrap();
+        listener
+            .set_read_timeout(Some(Duration::from_secs(5)))
+            .unwrap();
+        let reporter = StatsdReporter::new(listener.local_addr().unwrap()).unwrap();
+        let mut result = CloneResult {
+            url: "https://github.com/author/project.git".to_string(),
+            path: PathBuf::from("/base/path/github.com/author/project"),
+            success: true,
+            duration_ms: 1234,
+            error: None,
+        };
+
+        reporter.record_clone(&result).unwrap();
+        result.success = false;
+        result.duration_ms = 56;
+        reporter.record_clone(&result).unwrap();
+
+        assert_eq!(receive(&listener), "repo_cloner.clone.duration_ms:1234|ms");
+        assert_eq!(receive(&listener), "repo_cloner.clone.success:1|c");
+        assert_eq!(receive(&listener), "repo_cloner.clone.duration_ms:56|ms");
+        assert_eq!(receive(&listener), "repo_cloner.clone.failure:1|c");
+    }
+}
@@ -0,0 +1,145 @@
+#[cfg(unix)]
+use std::fs::File;
+use std::io::{self, Write};
+#[cfg(unix)]
+use std::os::unix::io::{AsRawFd, FromRawFd, OwnedFd};
+#[cfg(unix)]
+use std::thread::JoinHandle;
+
+/// Writes everything to two writers, like `tee(1)`.
+pub struct TeeWriter<A: Write, B: Write> {
+    primary: A,
+    copy: B,
+}
+
+impl<A: Write, B: Write> TeeWriter<A, B> {
+    pub fn new(primary: A, copy: B) -> Self {
+        TeeWriter { primary, copy }
+    }
+
+    pub fn into_inner(self) -> (A, B) {
+        (self.primary, self.copy)
+    }
+}
+
+impl<A: Write, B: Write> Write for TeeWriter<A, B> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.primary.write_all(buf)?;
+        self.copy.write_all(buf)?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.primary.flush()?;
+        self.copy.flush()
+    }
+}
+
+/// Copies everything written to this process's stderr into a file until
+/// dropped, including what child processes like git write, since they
+/// inherit it. Dropping it restores stderr and waits for the copy to catch up.
+#[cfg(unix)]
+pub struct StderrTee {
+    original: OwnedFd,
+    copier: Option<JoinHandle<()>>,
+}
+
+#[cfg(unix)]
+impl StderrTee {
+    /// Points stderr at a pipe whose contents are written to both the real
+    /// stderr and `copy`.
+    pub fn start(copy: File) -> io::Result<Self> {
+        let mut fds = [0; 2];
+        // SAFETY: `fds` has room for the two descriptors pipe() returns.
+        if unsafe { libc::pipe(fds.as_mut_ptr()) } != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        // SAFETY: pipe() just opened both descriptors and nothing else owns them.
+        let (read_end, write_end) =
+            unsafe { (OwnedFd::from_raw_fd(fds[0]), OwnedFd::from_raw_fd(fds[1])) };
+        let original = dup(libc::STDERR_FILENO)?;
+        let terminal = File::from(dup(original.as_raw_fd())?);
+        io::stderr().flush()?;
+        // SAFETY: both descriptors are open; dup2 only replaces fd 2.
+        if unsafe { libc::dup2(write_end.as_raw_fd(), libc::STDERR_FILENO) } < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        drop(write_end);
+
+        let copier = std::thread::spawn(move || {
+            let mut tee = TeeWriter::new(terminal, copy);
+            let _ = io::copy(&mut File::from(read_end), &mut tee);
+            let _ = tee.flush();
+        });
+        Ok(StderrTee {
+            original,
+            copier: Some(copier),
+        })
+    }
+}
+
+#[cfg(unix)]
+impl Drop for StderrTee {
+    fn drop(&mut self) {
+        let _ = io::stderr().flush();
+        // Putting the real stderr back closes the pipe's last write end we
+        // hold, so the copier sees end of file.
+        // SAFETY: `original` is an open descriptor we own.
+        unsafe { libc::dup2(self.original.as_raw_fd(), libc::STDERR_FILENO) };
+        if let Some(copier) = self.copier.take() {
+            let _ = copier.join();
+        }
+    }
+}
+
+#[cfg(unix)]
+fn dup(fd: i32) -> io::Result<OwnedFd> {
+    // SAFETY: dup() doesn't touch memory; a negative result is an error.
+    let duplicate = unsafe { libc::dup(fd) };
+    if duplicate < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    // SAFETY: dup() just returned this descriptor and nothing else owns it.
+    Ok(unsafe { OwnedFd::from_raw_fd(duplicate) })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::{self, File};
+
+    #[test]
+    fn test_writes_reach_both_writers() {
+        let file = tempfile::NamedTempFile::new().unwrap();
+        let mut tee = TeeWriter::new(Vec::new(), File::create(file.path()).unwrap());
+
+        writeln!(tee, "DRY RUN: mkdir -p /base/path").unwrap();
+        tee.flush().unwrap();
+
+        let (memory, _) = tee.into_inner();
+        assert_eq!(memory, b"DRY RUN: mkdir -p /base/path\n");
+        assert_eq!(
+            fs::read_to_string(file.path()).unwrap(),
+            "DRY RUN: mkdir -p /base/path\n"
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_stderr_tee_copies_our_and_child_stderr() {
+        let file = tempfile::NamedTempFile::new().unwrap();
+        let tee = StderrTee::start(File::create(file.path()).unwrap()).unwrap();
+
+        // Not eprintln!, which the test harness captures.
+        writeln!(io::stderr(), "Warning: from repo-cloner").unwrap();
+        std::process::Command::new("sh")
+            .args(["-c", "echo 'fatal: from git' >&2"])
+            .status()
+            .unwrap();
+        drop(tee);
+
+        let log = fs::read_to_string(file.path()).unwrap();
+        assert!(log.contains("Warning: from repo-cloner\n"));
+        assert!(log.contains("fatal: from git\n"));
+    }
+}
@@ -0,0 +1,88 @@
+use crate::output::CloneResult;
+use opentelemetry::global::BoxedTracer;
+use opentelemetry::trace::{Span, Status, TraceContextExt, Tracer, TracerProvider};
+use opentelemetry::{Context, ContextGuard, KeyValue};
+use opentelemetry_otlp::{ExporterBuildError, SpanExporter, WithExportConfig};
+use opentelemetry_sdk::trace::SdkTracerProvider;
+use std::time::{Duration, SystemTime};
+
+/// Path OTLP/HTTP collectors accept spans on.
+const TRACES_PATH: &str = "/v1/traces";
+
+/// Completes a collector URL such as `http://localhost:4318` with the
+/// traces path, leaving URLs that already name it alone.
+pub fn traces_endpoint(endpoint: &str) -> String {
+    let endpoint = endpoint.trim_end_matches('/');
+    if endpoint.ends_with(TRACES_PATH) {
+        endpoint.to_string()
+    } else {
+        format!("{}{}", endpoint, TRACES_PATH)
+    }
+}
+
+/// A tracer provider exporting each span to the OTLP/HTTP collector at
+/// `endpoint` as soon as it ends. Call `shutdown` before exiting.
+pub fn otlp_tracer_provider(endpoint: &str) -> Result<SdkTracerProvider, ExporterBuildError> {
+    let exporter = SpanExporter::builder()
+        .with_http()
+        .with_endpoint(traces_endpoint(endpoint))
+        .build()?;
+    Ok(SdkTracerProvider::builder()
+        .with_simple_exporter(exporter)
+        .build())
+}
+
+pub fn tracer(provider: &SdkTracerProvider) -> BoxedTracer {
+    BoxedTracer::new(Box::new(provider.tracer("repo-cloner")))
+}
+
+/// Starts the root span for a run. It stays the parent of clone spans
+/// until the returned guard is dropped, which also ends it.
+pub fn start_run(tracer: &BoxedTracer, git_url: &str) -> ContextGuard {
+    let mut span = tracer.start("repo-cloner run");
+    span.set_attribute(KeyValue::new("git.url", git_url.to_string()));
+    Context::current_with_span(span).attach()
+}
+
+/// Records a finished clone as a span ending now.
+pub fn record_clone(tracer: &BoxedTracer, result: &CloneResult) {
+    let duration_ms = u64::try_from(result.duration_ms).unwrap_or(u64::MAX);
+    let end = SystemTime::now();
+    let mut span = tracer
+        .span_builder("git clone")
+        .with_start_time(end - Duration::from_millis(duration_ms))
+        .with_attributes([
+            KeyValue::new("git.url", result.url.clone()),
+            KeyValue::new("git.clone_path", result.path.display().to_string()),
+            KeyValue::new(
+                "git.duration_ms",
+                i64::try_from(duration_ms).unwrap_or(i64::MAX),
+            ),
+        ])
+        .start(tracer);
+    if let Some(error) = &result.error {
+        span.set_status(Status::error(error.clone()));
+    }
+    span.end_with_timestamp(end);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_traces_endpoint() {
+        assert_eq!(
+            traces_endpoint("http://localhost:4318"),
+            "http://localhost:4318/v1/traces"
+        );
+        assert_eq!(
+            traces_endpoint("http://localhost:4318/"),
+            "http://localhost:4318/v1/traces"
+        );
+        assert_eq!(
+            traces_endpoint("http://collector/v1/traces"),
+            "http://collector/v1/traces"
+        );
+    }
+}
@@ -0,0 +1,58 @@
+//! A one-request HTTP server for testing API clients.
+
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpListener;
+use std::thread;
+
+/// What the server received.
+pub struct Request {
+    pub line: String,
+    pub headers: Vec<String>,
+    pub body: String,
+}
+
+/// Accepts one HTTP request on a local port and answers it with `status`
+/// (such as `201 Created`) and `body`. Returns the server's base URL and a
+/// handle yielding the request.
+pub fn serve_once(status: &str, body: &str) -> (String, thread::JoinHandle<Request>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let base = format!("http://{}", listener.local_addr().unwrap());
+    let response = format!(
+        "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{}",
+        status,
+        body.len(),
+        body
+    );
+    let handle = thread::spawn(move || {
+        let (stream, _) = listener.accept().unwrap();
+        let mut reader = BufReader::new(stream.try_clone().unwrap());
+        let mut line = String::new();
+        reader.read_line(&mut line).unwrap();
+        let mut headers = Vec::new();
+        loop {
+            let mut header = String::new();
+            reader.read_line(&mut header).unwrap();
+            if header.trim_end().is_empty() {
+                break;
+            }
+            headers.push(header.trim_end().to_string());
+        }
+        let length: usize = headers
+            .iter()
+            .find_map(|header| {
+                let (name, value) = header.split_once(':')?;
+                name.eq_ignore_ascii_case("content-length")
+                    .then(|| value.trim().parse().unwrap())
+            })
+            .unwrap_or(0);
+        let mut body = vec![0; length];
+        reader.read_exact(&mut body).unwrap();
+        (&stream).write_all(response.as_bytes()).unwrap();
+        Request {
+            line: line.trim_end().to_string(),
+            headers,
+            body: String::from_utf8(body).unwrap(),
+        }
+    });
+    (base, handle)
+}
@@ -0,0 +1,117 @@
+use serde::Deserialize;
+use std::fmt;
+use std::io;
+
+/// A `username`/`password` pair read from Vault.
+#[derive(Clone, PartialEq, Deserialize)]
+pub struct Credentials {
+    pub username: String,
+    pub password: String,
+}
+
+/// Written by hand so the password never ends up in logs or panics.
+impl fmt::Debug for Credentials {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Credentials")
+            .field("username", &self.username)
+            .field("password", &"***")
+            .finish()
+    }
+}
+
+#[derive(Deserialize)]
+struct SecretResponse {
+    data: SecretData,
+}
+
+#[derive(Deserialize)]
+struct SecretData {
+    data: Credentials,
+}
+
+/// Reads git credentials from a HashiCorp Vault KV version 2 engine.
+pub struct VaultClient {
+    addr: String,
+    token: String,
+}
+
+impl VaultClient {
+    pub fn new(addr: &str, token: &str) -> Self {
+        VaultClient {
+            addr: addr.trim_end_matches('/').to_string(),
+            token: token.to_string(),
+        }
+    }
+
+    /// Reads the secret at `path`, written as `<mount>/<secret>` like
+    /// `vault kv get` takes it (for example `secret/git/github`).
+    pub fn read_credentials(&self, path: &str) -> io::Result<Credentials> {
+        let (mount, secret) = path.trim_matches('/').split_once('/').ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Vault path {} should be <mount>/<secret>", path),
+            )
+        })?;
+        let response: SecretResponse =
+            ureq::get(&format!("{}/v1/{}/data/{}", self.addr, mount, secret))
+                .set("X-Vault-Token", &self.token)
+                .call()
+                .map_err(|err| {
+                    io::Error::other(format!("failed to read {} from Vault: {}", path, err))
+                })?
+                .into_json()?;
+        Ok(response.data.data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_http::serve_once;
+
+    #[test]
+    fn test_read_credentials_from_kv2() {
+        let (addr, server) = serve_once(
+            "200 OK",
+            r#"{"data":{"data":{"username":"alice","password":"s3cret"},"metadata":{}}}"#,
+        );
+
+        let credentials = VaultClient::new(&addr, "vault-token")
+            .read_credentials("secret/git/github")
+            .unwrap();
+
+        assert_eq!(
+            credentials,
+            Credentials {
+                username: "alice".to_string(),
+                password: "s3cret".to_string(),
+            }
+        );
+        let request = server.join().unwrap();
+        assert_eq!(request.line, "GET /v1/secret/data/git/github HTTP/1.1");
+        assert!(request
+            .headers
+            .iter()
+            .any(|header| header == "X-Vault-Token: vault-token"));
+    }
+
+    #[test]
+    fn test_read_credentials_rejects_paths_without_a_mount() {
+        let err = VaultClient::new("http://127.0.0.1:1", "vault-token")
+            .read_credentials("github")
+            .unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn test_debug_hides_the_password() {
+        let credentials = Credentials {
+            username: "alice".to_string(),
+            password: "s3cret".to_string(),
+        };
+        assert_eq!(
+            format!("{:?}", credentials),
+            r#"Credentials { username: "alice", password: "***" }"#
+        );
+    }
+}